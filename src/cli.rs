@@ -1,31 +1,523 @@
-use clap::{Parser, Subcommand};
+use crate::export::ExportFormat;
+use crate::service::ServiceKind;
+use chrono::NaiveDate;
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::env;
+use std::ffi::OsString;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+const NO_COLOR_ENV_VAR: &str = "NO_COLOR";
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Path to the config file, overrides STUDY_TIMER_CONFIG
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Named profile to use, each profile has its own config
+    #[arg(long, global = true, conflicts_with = "config")]
+    pub profile: Option<String>,
+
+    /// Disable colored output, NO_COLOR is honored as well
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Draw progress bars with # and - instead of block characters
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Only print errors and the output that was asked for, like lists and json
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print extra detail, like the resolved config path and how long the command took
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Check and show what a command would change without saving anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Cli {
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (_, true) => Verbosity::Verbose,
+            _ => Verbosity::Normal,
+        }
+    }
+
+    pub fn color_enabled(&self) -> bool {
+        Self::choose_color(self.no_color, env::var_os(NO_COLOR_ENV_VAR))
+    }
+
+    // any non-empty NO_COLOR disables color, see https://no-color.org
+    fn choose_color(no_color: bool, no_color_env: Option<OsString>) -> bool {
+        !no_color && no_color_env.filter(|value| !value.is_empty()).is_none()
+    }
+
+    pub fn write_completions<W: Write>(shell: Shell, writer: &mut W) {
+        let mut command = Self::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, writer);
+    }
+}
+
 #[derive(Subcommand, PartialEq)]
 pub enum Commands {
-    Init,
+    /// Reset the config to the defaults
+    Init {
+        /// Skip the confirmation prompt when a config already exists
+        #[arg(short, long)]
+        yes: bool,
+    },
     Add {
         subject: String,
 
         #[arg(short, long)]
         target_hours: f32,
+
+        /// Hours already studied before tracking started
+        #[arg(short, long, default_value_t = 0.0)]
+        completed: f32,
+
+        /// Date to reach the target by, YYYY-MM-DD
+        #[arg(long)]
+        deadline: Option<NaiveDate>,
+
+        /// Hours to study each week, counted from the week-start setting
+        #[arg(short, long)]
+        weekly_target: Option<f32>,
+
+        /// Tag to group the subject under, can be repeated
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+
+        /// Overwrite an existing subject, resetting its completed hours
+        #[arg(long)]
+        force: bool,
+    },
+    #[command(group(ArgGroup::new("changes").required(true).multiple(true).args(["target_hours", "deadline", "weekly_target", "tags"])))]
+    Edit {
+        subject: String,
+
+        #[arg(short, long)]
+        target_hours: Option<f32>,
+
+        /// Date to reach the target by, YYYY-MM-DD
+        #[arg(long)]
+        deadline: Option<NaiveDate>,
+
+        /// Hours to study each week, counted from the week-start setting
+        #[arg(short, long)]
+        weekly_target: Option<f32>,
+
+        /// Replace the subject's tags, can be repeated
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+    },
+    Rename {
+        old: String,
+        new: String,
+    },
+    Remove {
+        subject: String,
+    },
+    /// Remove every subject and scheduled session, settings are kept
+    Clear {
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    Log {
+        subject: String,
+        hours: f32,
+    },
+    /// Overwrite a subject's completed hours, to correct a wrong total
+    SetHours {
+        subject: String,
+        hours: f32,
     },
     Schedule {
         subject: String,
         day:String,
         start_time: String,
 
+        /// Minutes, or a duration like 90m, 1.5h or 1h30m
+        #[arg(short, long, value_parser = parse_duration)]
+        duration: u32,
+
+        /// Only on this date (YYYY-MM-DD) instead of every week, the day must match it
+        #[arg(long, value_name = "DATE")]
+        once: Option<NaiveDate>,
+
+        /// Minutes of warning before these sessions, overrides the reminder-lead-minutes setting
+        #[arg(long, value_name = "MINUTES")]
+        lead: Option<u32>,
+    },
+    Suggest {
+        /// Hours available for studying each week
         #[arg(short, long)]
+        budget: f32,
+
+        /// Length of each suggested session in minutes
+        #[arg(short = 'l', long, default_value_t = 60)]
+        session_length: u32,
+
+        /// Add the suggested sessions to the schedule
+        #[arg(long)]
+        apply: bool,
+    },
+    RemoveSchedule {
+        subject: String,
+        day: String,
+        start_time: String,
+    },
+    /// Move a session to another day or start time, keeping its duration
+    MoveSchedule {
+        subject: String,
+        day: String,
+        start_time: String,
+        new_day: String,
+        new_start_time: String,
+    },
+    /// Copy a subject's weekly sessions from one day to another, e.g. Tuesday to Thursday
+    CopySchedule {
+        subject: String,
+        from_day: String,
+
+        /// A day, a comma separated list of days, weekdays or weekends
+        to_day: String,
+    },
+    /// Pick up a focus session that a closed terminal or crash cut short
+    ResumeFocus,
+    Focus {
+        subject: String,
+
+        /// Minutes, or a duration like 90m, 1.5h or 1h30m
+        #[arg(short, long, value_parser = parse_duration)]
         duration: u32,
     },
-    List,
-    Start,
+    Pomodoro {
+        subject: String,
+
+        #[arg(short, long, default_value_t = 25)]
+        work: u32,
+
+        #[arg(short, long, default_value_t = 5)]
+        breaks: u32,
+
+        #[arg(short, long, default_value_t = 4)]
+        rounds: u32,
+    },
+    Set {
+        key: String,
+        value: String,
+    },
+    List {
+        /// Print subjects and sessions as JSON instead of text
+        #[arg(long)]
+        json: bool,
+
+        /// Only list subjects with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only list this subject, can be repeated
+        #[arg(long = "subject", value_name = "SUBJECT")]
+        subjects: Vec<String>,
+
+        /// Only list sessions on this day, can be repeated and takes weekdays or weekends
+        #[arg(long = "day", value_name = "DAY")]
+        days: Vec<String>,
+    },
+    Next,
+    /// Print a one-line summary for shell prompts and status bars
+    Summary,
+    Today,
+    Conflicts,
+    Start {
+        /// Also write logs to a daily rotated file under the cache directory
+        #[arg(long)]
+        log_file: bool,
+
+        /// Fork into the background, output goes to a file under the cache directory. Unix only
+        #[arg(long)]
+        daemonize: bool,
+
+        /// Serve Prometheus metrics on this port at 127.0.0.1/metrics, off unless given
+        #[arg(long, value_name = "PORT")]
+        metrics_port: Option<u16>,
+    },
     Stop,
-    Progress,
+    Pause {
+        /// Date reminders resume on, YYYY-MM-DD, paused until resume when omitted
+        until: Option<NaiveDate>,
+    },
+    Resume,
+    Status,
+    Streak,
+    Stats,
+    History {
+        /// Only show sessions for this subject
+        #[arg(short, long)]
+        subject: Option<String>,
+
+        /// Only show sessions on or after this date, YYYY-MM-DD
+        #[arg(long)]
+        since: Option<NaiveDate>,
+
+        /// Number of most recent sessions to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    Progress {
+        /// Print progress as JSON instead of text
+        #[arg(long)]
+        json: bool,
+
+        /// Average each subject's percentage equally instead of weighting by target hours
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        balanced: bool,
+
+        /// Only show subjects with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Count only sessions from this date on, YYYY-MM-DD, using the session history instead of lifetime totals
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Count only sessions up to and including this date, YYYY-MM-DD
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    Doctor,
+    /// Look for problems in the config, like sessions for missing subjects or overlapping sessions
+    Check,
+    /// Send a single notification to check that reminders can reach you
+    TestNotify,
+    Restore,
+    /// Revert the last change by restoring the backup taken before it, and show what that puts back
+    Undo,
+    /// Live dashboard with progress, today's sessions and the daemon status, q quits
+    Tui,
+    /// Serve subjects, progress and the next session as JSON over HTTP, and accept logged hours
+    Serve {
+        /// Address to listen on, only this machine can connect by default
+        #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+        host: IpAddr,
+
+        #[arg(short, long, default_value_t = 7878)]
+        port: u16,
+    },
+    Profiles,
+    Export {
+        #[arg(value_enum)]
+        format: ExportFormat,
+
+        /// File to write to, stdout when omitted
+        path: Option<PathBuf>,
+    },
+    /// Add subjects from a csv file of name,target_hours lines or a json array
+    Import {
+        path: PathBuf,
+
+        /// Update the target of subjects that already exist instead of skipping them
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Print a service unit that keeps the daemon running, e.g. for systemctl --user
+    Service {
+        #[arg(value_enum)]
+        kind: ServiceKind,
+    },
+    /// Print a shell completion script to stdout
+    ///
+    /// bash:       add `source <(study_timer completions bash)` to ~/.bashrc
+    /// zsh:        study_timer completions zsh > "${fpath[1]}/_study_timer"
+    /// fish:       study_timer completions fish > ~/.config/fish/completions/study_timer.fish
+    /// powershell: add `study_timer completions powershell | Out-String | Invoke-Expression` to $PROFILE
+    #[command(verbatim_doc_comment)]
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+impl Commands {
+    // these act on the running system, send notifications or write files other than through the scheduler, so there is nothing to preview
+    pub fn supports_dry_run(&self) -> bool {
+        !matches!(self,
+                  Commands::Init { .. }
+                  | Commands::Restore
+                  | Commands::Undo
+                  | Commands::Start { .. }
+                  | Commands::Serve { .. }
+                  | Commands::Stop
+                  | Commands::Focus { .. }
+                  | Commands::Pomodoro { .. }
+                  | Commands::ResumeFocus
+                  | Commands::TestNotify
+                  | Commands::Export { path: Some(_), .. })
+    }
+}
+
+fn parse_tag(value: &str) -> Result<String, String> {
+    let tag = value.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("tags can not be empty".to_string());
+    }
+
+    Ok(tag)
+}
+
+// plain minutes are kept for existing scripts, "90m", "1.5h" and "1h30m" read more naturally
+fn parse_duration(value: &str) -> Result<u32, String> {
+    let normalized = value.trim().to_lowercase();
+    if let Ok(minutes) = normalized.parse::<u32>() {
+        return Ok(minutes);
+    }
+
+    let invalid = || format!("invalid duration '{}', expected minutes or a duration like 90m, 1.5h or 1h30m", value);
+
+    let (hours, minutes) = match normalized.split_once('h') {
+        Some((hours, rest)) => (Some(hours), rest.strip_suffix('m').unwrap_or(rest)),
+        None => (None, normalized.strip_suffix('m').ok_or_else(invalid)?),
+    };
+
+    let hours = match hours {
+        Some(hours) => hours.parse::<f64>().ok().filter(|hours| hours.is_finite() && *hours >= 0.0).ok_or_else(invalid)?,
+        None => 0.0,
+    };
+
+    let minutes = match minutes {
+        "" if hours > 0.0 => 0,
+        "" => return Err(invalid()),
+        minutes => {
+            let minutes = minutes.parse::<u32>().map_err(|_| invalid())?;
+            // minutes next to hours have to stay under an hour, "1h70m" is most likely a typo
+            if hours > 0.0 && (minutes >= 60 || hours.fract() != 0.0) {
+                return Err(invalid());
+            }
+            minutes
+        },
+    };
+
+    let total = (hours * 60.0).round() + minutes as f64;
+    if total > u32::MAX as f64 {
+        return Err(format!("duration '{}' is too long", value));
+    }
+
+    Ok(total as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_color() {
+        assert!(Cli::choose_color(false, None));
+        assert!(Cli::choose_color(false, Some(OsString::new())));
+        assert!(!Cli::choose_color(true, None));
+        assert!(!Cli::choose_color(false, Some(OsString::from("1"))));
+        assert!(!Cli::choose_color(true, Some(OsString::from("1"))));
+    }
+
+    #[test]
+    fn test_write_completions() {
+        let mut output = Vec::new();
+        Cli::write_completions(Shell::Bash, &mut output);
+
+        let script = String::from_utf8(output).unwrap();
+        assert!(script.contains("study_timer"));
+        assert!(script.contains("remove-schedule"));
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let cli = Cli::try_parse_from(["study_timer", "list"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Normal);
+
+        // global, so they work after the subcommand too
+        let cli = Cli::try_parse_from(["study_timer", "list", "-q"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Quiet);
+        let cli = Cli::try_parse_from(["study_timer", "--verbose", "list"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Verbose);
+
+        assert!(Cli::try_parse_from(["study_timer", "-q", "-v", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let cli = Cli::try_parse_from(["study_timer", "clear", "--dry-run"]).unwrap();
+        assert!(cli.dry_run);
+        assert!(cli.command.supports_dry_run());
+
+        let cli = Cli::try_parse_from(["study_timer", "--dry-run", "focus", "dsa", "-d", "25"]).unwrap();
+        assert!(!cli.command.supports_dry_run());
+
+        let cli = Cli::try_parse_from(["study_timer", "--dry-run", "test-notify"]).unwrap();
+        assert!(!cli.command.supports_dry_run());
+
+        let cli = Cli::try_parse_from(["study_timer", "--dry-run", "export", "csv", "out.csv"]).unwrap();
+        assert!(!cli.command.supports_dry_run());
+
+        let cli = Cli::try_parse_from(["study_timer", "--dry-run", "export", "csv"]).unwrap();
+        assert!(cli.command.supports_dry_run());
+
+        let cli = Cli::try_parse_from(["study_timer", "init", "-y"]).unwrap();
+        assert!(cli.command == Commands::Init { yes: true });
+        assert!(!cli.command.supports_dry_run());
+    }
+
+    #[test]
+    fn test_tag_flags() {
+        let cli = Cli::try_parse_from(["study_timer", "add", "dsa", "-t", "10", "--tag", "University", "--tag", "cs"]).unwrap();
+        assert!(matches!(cli.command, Commands::Add { ref tags, .. } if tags == &["university", "cs"]));
+
+        // tags alone are enough for edit
+        assert!(Cli::try_parse_from(["study_timer", "edit", "dsa", "--tag", "hobby"]).is_ok());
+        assert!(Cli::try_parse_from(["study_timer", "add", "dsa", "-t", "10", "--tag", " "]).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert_eq!(parse_duration("90m"), Ok(90));
+        assert_eq!(parse_duration("1.5h"), Ok(90));
+        assert_eq!(parse_duration("1h30m"), Ok(90));
+        assert_eq!(parse_duration("1h30"), Ok(90));
+        assert_eq!(parse_duration("2H"), Ok(120));
+        assert_eq!(parse_duration("0h45m"), Ok(45));
+        assert_eq!(parse_duration(" 45m "), Ok(45));
+
+        for invalid in ["", "h", "m", "1h70m", "1.5h30m", "-90", "-1h", "-30m", "1.5", "90s", "1h30m15s", "abc", "NaNh"] {
+            assert!(parse_duration(invalid).is_err(), "{} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn test_duration_flag() {
+        let cli = Cli::try_parse_from(["study_timer", "schedule", "dsa", "Monday", "09:00", "-d", "1h30m"]).unwrap();
+        assert!(matches!(cli.command, Commands::Schedule { duration: 90, .. }));
+
+        assert!(Cli::try_parse_from(["study_timer", "focus", "dsa", "-d", "1h70m"]).is_err());
+    }
 }
@@ -1,15 +1,117 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
-use std::error::Error;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 use directories::ProjectDirs;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Weekday};
+use chrono_tz::Tz;
+use crate::error::StudyTimerError;
+use crate::notification::{Backend, Urgency};
+use tokio::io::AsyncWriteExt;
+
+const CONFIG_ENV_VAR: &str = "STUDY_TIMER_CONFIG";
+const TOML_EXTENSION: &str = "toml";
+pub const DEFAULT_PROFILE: &str = "default";
+const MAX_SESSION_MINUTES: u32 = 24 * 60;
+const MINUTES_PER_WEEK: i64 = 7 * 24 * 60;
+// how long to wait on another process holding the config lock before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY: Duration = Duration::from_millis(50);
+static VALID_DAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+const SETTINGS: [&str; 15] = ["reminder-lead-minutes", "notification-icon", "notification-timeout-ms", "notification-urgency", "notification-backend", "webhook-url", "progress-colors", "neglect-days", "dnd", "digest-time", "digest-empty-days", "reminder-template", "pre-reminder-template", "week-start", "timezone"];
+const TEMPLATE_PLACEHOLDERS: [&str; 4] = ["subject", "duration", "time", "lead"];
+
+fn default_reminder_lead_minutes() -> u32 {
+    5
+}
+
+fn default_notification_icon() -> String {
+    "clock".to_string()
+}
+
+fn default_notification_timeout_ms() -> u32 {
+    10000
+}
+
+fn default_progress_colors() -> bool {
+    true
+}
+
+fn default_neglect_days() -> u32 {
+    5
+}
+
+fn default_week_start() -> Weekday {
+    Weekday::Mon
+}
+
+fn default_reminder_template() -> String {
+    "Time to study {subject} for {duration} minutes".to_string()
+}
+
+fn default_pre_reminder_template() -> String {
+    "{subject} study session starts in {lead} minutes".to_string()
+}
+
+// picked from the config file extension, anything that isn't .toml is json
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case(TOML_EXTENSION) => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    fn parse(self, contents: &str, path: &Path) -> Result<Config, StudyTimerError> {
+        let corrupted = |source: Box<dyn std::error::Error + Send + Sync>| StudyTimerError::ConfigCorrupted { path: path.to_path_buf(), source };
+
+        match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| corrupted(e.into())),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|e| corrupted(e.into())),
+        }
+    }
+
+    fn serialize(self, config: &Config) -> Result<String, StudyTimerError> {
+        match self {
+            ConfigFormat::Json => Ok(serde_json::to_string_pretty(config)?),
+            ConfigFormat::Toml => Ok(toml::to_string_pretty(config)?),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Subject {
     pub target_hours: f32,
     pub completed_hours: f32,
+    #[serde(default)]
+    pub last_studied: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub deadline: Option<NaiveDate>,
+    #[serde(default)]
+    pub weekly_target: Option<f32>,
+    // hours logged since the last weekly reset
+    #[serde(default)]
+    pub weekly_completed: f32,
+    // lowercase, e.g. "university" or "hobby"
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Subject {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own.eq_ignore_ascii_case(tag.trim()))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -17,6 +119,98 @@ pub struct StudySession {
     pub day: String,
     pub start_time: String,
     pub duration: u32,
+    #[serde(default)]
+    pub end_time: String,
+    // set for a one-off session, which only happens on this date instead of every week
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    // minutes of warning before this session, reminder_lead_minutes applies when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lead_minutes: Option<u32>,
+}
+
+impl StudySession {
+    pub fn new(day: &str, start_time: &str, duration: u32) -> Self {
+        Self {
+            day: day.to_string(),
+            start_time: start_time.to_string(),
+            duration,
+            end_time: Self::compute_end_time(start_time, duration),
+            date: None,
+            lead_minutes: None,
+        }
+    }
+
+    pub fn lead(&self, default_minutes: u32) -> u32 {
+        self.lead_minutes.unwrap_or(default_minutes)
+    }
+
+    // the day is kept in step with the date so the weekly views still place it
+    pub fn once(date: NaiveDate, start_time: &str, duration: u32) -> Self {
+        Self {
+            date: Some(date),
+            ..Self::new(VALID_DAYS[date.weekday().num_days_from_monday() as usize], start_time, duration)
+        }
+    }
+
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        match self.date {
+            Some(once) => once == date,
+            None => VALID_DAYS[date.weekday().num_days_from_monday() as usize] == self.day,
+        }
+    }
+
+    // weekly sessions never expire, a one-off one only once it is over, which can be after midnight
+    pub fn expired(&self, now: NaiveDateTime) -> bool {
+        let Some(date) = self.date else {
+            return false;
+        };
+
+        match NaiveTime::parse_from_str(&self.start_time, "%H:%M") {
+            Ok(start_time) => date.and_time(start_time) + chrono::Duration::minutes(self.duration as i64) < now,
+            Err(_) => date < now.date(),
+        }
+    }
+
+    // e.g. "Monday" or "Monday 2024-01-22" for a one-off session
+    pub fn describe_day(&self) -> String {
+        match self.date {
+            Some(date) => format!("{} {}", self.day, date),
+            None => self.day.clone(),
+        }
+    }
+
+    // the end time wraps past midnight, e.g. 23:30 for 60 minutes ends at 00:30
+    fn compute_end_time(start_time: &str, duration: u32) -> String {
+        NaiveTime::parse_from_str(start_time, "%H:%M")
+            .map(|start| (start + chrono::Duration::minutes(duration as i64)).format("%H:%M").to_string())
+            .unwrap_or_default()
+    }
+
+    // start and end of the session in minutes since Monday 00:00, the end may spill into the next week
+    pub fn week_interval(&self) -> Option<(i64, i64)> {
+        let day = VALID_DAYS.iter().position(|valid| *valid == self.day)? as i64;
+        let start_time = NaiveTime::parse_from_str(&self.start_time, "%H:%M").ok()?;
+        let start = day * 24 * 60 + (start_time.hour() * 60 + start_time.minute()) as i64;
+
+        Some((start, start + self.duration as i64))
+    }
+
+    pub fn overlaps(&self, other: &StudySession) -> bool {
+        let (Some((start, end)), Some((other_start, other_end))) = (self.week_interval(), other.week_interval()) else {
+            return false;
+        };
+
+        // two one-off sessions can only meet when their dates are at most a day apart, across midnight
+        if let (Some(date), Some(other_date)) = (self.date, other.date) && (date - other_date).num_days().abs() > 1 {
+            return false;
+        }
+
+        // shift by a week either way so a sunday night session can clash with monday morning
+        [-MINUTES_PER_WEEK, 0, MINUTES_PER_WEEK]
+            .iter()
+            .any(|shift| start < other_end + shift && other_start + shift < end)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,6 +218,56 @@ pub struct Config {
     pub subjects: HashMap<String, Subject>,
     pub schedules: HashMap<String, Vec<StudySession>>,
     pub config_path: PathBuf,
+    #[serde(default = "default_reminder_lead_minutes")]
+    pub reminder_lead_minutes: u32,
+    #[serde(default = "default_notification_icon")]
+    pub notification_icon: String,
+    #[serde(default = "default_notification_timeout_ms")]
+    pub notification_timeout_ms: u32,
+    #[serde(default)]
+    pub notification_urgency: Urgency,
+    #[serde(default)]
+    pub notification_backend: Backend,
+    // where the webhook backend posts to
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_progress_colors")]
+    pub progress_colors: bool,
+    #[serde(default = "default_neglect_days")]
+    pub neglect_days: u32,
+    // do-not-disturb window as HH:MM, may wrap past midnight
+    #[serde(default)]
+    pub dnd_start: Option<String>,
+    #[serde(default)]
+    pub dnd_end: Option<String>,
+    // HH:MM the daemon sends the day's agenda at, no agenda when unset
+    #[serde(default)]
+    pub digest_time: Option<String>,
+    // also send the agenda on days with nothing scheduled
+    #[serde(default)]
+    pub digest_empty_days: bool,
+    // set by pause, without a date the daemon stays quiet until resume
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub paused_until: Option<NaiveDate>,
+    // local dates with any logged study, streaks are computed from these
+    #[serde(default)]
+    pub study_days: BTreeSet<NaiveDate>,
+    // first day of the week weekly_completed was last zeroed for
+    #[serde(default)]
+    pub last_weekly_reset: Option<NaiveDate>,
+    // weekly progress resets when a week starting on this day begins
+    #[serde(default = "default_week_start")]
+    pub week_start: Weekday,
+    // sessions are matched against this zone's wall clock, the system zone when unset
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+    // notification text, placeholders are filled in when the reminder goes out
+    #[serde(default = "default_reminder_template")]
+    pub reminder_template: String,
+    #[serde(default = "default_pre_reminder_template")]
+    pub pre_reminder_template: String,
 }
 
 impl Default for Config {
@@ -33,220 +277,2429 @@ impl Default for Config {
             subjects: HashMap::new(),
             schedules: HashMap::new(),
             config_path,
+            reminder_lead_minutes: default_reminder_lead_minutes(),
+            notification_icon: default_notification_icon(),
+            notification_timeout_ms: default_notification_timeout_ms(),
+            notification_urgency: Urgency::default(),
+            notification_backend: Backend::default(),
+            webhook_url: None,
+            progress_colors: default_progress_colors(),
+            neglect_days: default_neglect_days(),
+            dnd_start: None,
+            dnd_end: None,
+            digest_time: None,
+            digest_empty_days: false,
+            paused: false,
+            paused_until: None,
+            study_days: BTreeSet::new(),
+            last_weekly_reset: None,
+            week_start: default_week_start(),
+            timezone: None,
+            reminder_template: default_reminder_template(),
+            pre_reminder_template: default_pre_reminder_template(),
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self, Box<dyn Error>> {
-        let config_path = Self::get_config_path();
+    // an explicit --config path wins over --profile, then STUDY_TIMER_CONFIG, then the platform default
+    pub fn resolve_config_path(config_path: Option<PathBuf>, profile: Option<&str>) -> Result<PathBuf, StudyTimerError> {
+        match (config_path, profile) {
+            (None, Some(profile)) => Self::profile_path(profile, &Self::profiles_dir()),
+            (config_path, _) => Ok(Self::choose_config_path(config_path, env::var_os(CONFIG_ENV_VAR))),
+        }
+    }
+
+    // the default profile keeps living at the original config path so existing setups carry over
+    fn profile_path(profile: &str, profiles_dir: &Path) -> Result<PathBuf, StudyTimerError> {
+        Self::validate_profile(profile)?;
+
+        if profile == DEFAULT_PROFILE {
+            return Ok(Self::get_config_path());
+        }
+
+        Ok(profiles_dir.join(format!("{}.json", profile)))
+    }
+
+    fn validate_profile(profile: &str) -> Result<(), StudyTimerError> {
+        let valid = !profile.is_empty()
+            && profile.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+        if !valid {
+            return Err(StudyTimerError::InvalidProfile(format!("invalid profile name '{}', use letters, digits, '-' and '_' only", profile)));
+        }
+
+        Ok(())
+    }
+
+    pub fn list_profiles() -> Result<Vec<String>, StudyTimerError> {
+        Self::list_profiles_in(&Self::profiles_dir())
+    }
+
+    fn list_profiles_in(profiles_dir: &Path) -> Result<Vec<String>, StudyTimerError> {
+        let mut profiles = Vec::new();
+
+        if profiles_dir.exists() {
+            for entry in fs::read_dir(profiles_dir)? {
+                let path = entry?.path();
+                let is_json = path.extension().and_then(|extension| extension.to_str()) == Some("json");
+
+                if let (true, Some(name)) = (is_json, path.file_stem().and_then(|stem| stem.to_str())) && name != DEFAULT_PROFILE && Self::validate_profile(name).is_ok() {
+                    profiles.push(name.to_string());
+                }
+            }
+        }
+
+        profiles.sort();
+        profiles.insert(0, DEFAULT_PROFILE.to_string());
+
+        Ok(profiles)
+    }
+
+    fn profiles_dir() -> PathBuf {
+        if let Some(project_directories) = ProjectDirs::from("com", "study_timer", "study_timer") {
+            project_directories.config_dir().join("profiles")
+        } else {
+            PathBuf::from("./study_timer_profiles")
+        }
+    }
+
+    fn choose_config_path(config_path: Option<PathBuf>, env_path: Option<OsString>) -> PathBuf {
+        config_path
+            .or_else(|| env_path.filter(|path| !path.is_empty()).map(PathBuf::from))
+            .unwrap_or_else(Self::get_config_path)
+    }
 
+    pub fn load_from(config_path: &Path) -> Result<Self, StudyTimerError> {
         if !config_path.exists() {
-            return Err("config file not found".into());
+            return Err(StudyTimerError::ConfigNotFound(config_path.to_path_buf()));
+        }
+
+        let _lock = Self::lock(config_path, false)?;
+        let config_str = fs::read_to_string(config_path)?;
+        Self::from_loaded(config_path, &config_str)
+    }
+
+    // for the daemon, which shouldn't block the runtime thread on file I/O
+    pub async fn load_async(config_path: &Path) -> Result<Self, StudyTimerError> {
+        if !tokio::fs::try_exists(config_path).await? {
+            return Err(StudyTimerError::ConfigNotFound(config_path.to_path_buf()));
+        }
+
+        let _lock = Self::lock_async(config_path, false).await?;
+        let config_str = tokio::fs::read_to_string(config_path).await?;
+        Self::from_loaded(config_path, &config_str)
+    }
+
+    // an advisory lock on a sibling .lock file, shared for reads and exclusive for writes.
+    // the config itself can't carry the lock, saving renames a new file over it.
+    // released when the returned file is dropped
+    fn lock(config_path: &Path, exclusive: bool) -> Result<File, StudyTimerError> {
+        Self::lock_within(config_path, exclusive, LOCK_TIMEOUT)
+    }
+
+    fn lock_within(config_path: &Path, exclusive: bool, timeout: Duration) -> Result<File, StudyTimerError> {
+        let lock_file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(Self::sibling_path(config_path, ".lock"))?;
+
+        let started = Instant::now();
+        loop {
+            let locked = if exclusive { lock_file.try_lock() } else { lock_file.try_lock_shared() };
+
+            match locked {
+                Ok(()) => return Ok(lock_file),
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+                Err(TryLockError::WouldBlock) if started.elapsed() >= timeout => return Err(StudyTimerError::ConfigBusy(config_path.to_path_buf())),
+                Err(TryLockError::WouldBlock) => thread::sleep(LOCK_RETRY),
+            }
         }
+    }
 
-        let config_str = fs::read_to_string(&config_path)?;
-        let mut config: Config = serde_json::from_str(&config_str)?;
-        config.config_path = config_path;
+    // waiting on the lock blocks, so it is kept off the runtime thread
+    async fn lock_async(config_path: &Path, exclusive: bool) -> Result<File, StudyTimerError> {
+        let config_path = config_path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || Self::lock(&config_path, exclusive))
+            .await
+            .map_err(|e| StudyTimerError::Io(io::Error::other(e)))?
+    }
+
+    fn from_loaded(config_path: &Path, config_str: &str) -> Result<Self, StudyTimerError> {
+        let mut config = ConfigFormat::from_path(config_path).parse(config_str, config_path)?;
+        config.config_path = config_path.to_path_buf();
+
+        // duration is the source of truth, this also backfills configs saved before end_time existed
+        for session in config.schedules.values_mut().flatten() {
+            session.end_time = StudySession::compute_end_time(&session.start_time, session.duration);
+        }
 
         Ok(config)
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+    pub fn save(&self) -> Result<(), StudyTimerError> {
         if let Some(parent) = self.config_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let config_str = serde_json::to_string_pretty(self)?;
-        let mut file = File::create(&self.config_path)?;
-        file.write_all(config_str.as_bytes())?;
+        let config_str = ConfigFormat::from_path(&self.config_path).serialize(self)?;
+        let _lock = Self::lock(&self.config_path, true)?;
+
+        // keep the previous generation around, there is nothing to back up on the first save
+        if self.config_path.exists() {
+            fs::copy(&self.config_path, Self::backup_path(&self.config_path))?;
+        }
+
+        // write a sibling file and rename it over the config, a crash mid-write never truncates the original
+        let temp_path = Self::sibling_path(&self.config_path, ".tmp");
+        if let Err(e) = Self::write_synced(&temp_path, config_str.as_bytes()) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        fs::rename(&temp_path, &self.config_path)?;
 
         Ok(())
     }
 
-    pub fn add_subject(&mut self, name: &str, target_hours: f32) -> Result<(), Box<dyn Error>> {
-        if target_hours <= 0.0 {
-            return Err("you must set a target time for yoyr study".into());
+    fn write_synced(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+
+    // same steps as save but without the backup: only the daemon saves this way, and its
+    // bookkeeping shouldn't replace the undo point of the user's last command
+    pub async fn save_async(&self) -> Result<(), StudyTimerError> {
+        if let Some(parent) = self.config_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
         }
 
-        self.subjects.insert(name.to_string(), Subject {
-            target_hours,
-            completed_hours: 0.0,
-        });
+        let config_str = ConfigFormat::from_path(&self.config_path).serialize(self)?;
+        let _lock = Self::lock_async(&self.config_path, true).await?;
+
+        let temp_path = Self::sibling_path(&self.config_path, ".tmp");
+        if let Err(e) = Self::write_synced_async(&temp_path, config_str.as_bytes()).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e.into());
+        }
+        tokio::fs::rename(&temp_path, &self.config_path).await?;
 
         Ok(())
     }
 
-    pub fn add_schedule(&mut self, subject: &str, day: &str, start_time: &str, duration: u32) -> Result<(), Box<dyn Error>> {
-        if !self.subjects.contains_key(subject) {
-            return Err(format!("subject '{}' not found..", subject).into());
-        }
+    async fn write_synced_async(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut file = tokio::fs::File::create(path).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await
+    }
 
-        let valid_days = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
-        if !valid_days.contains(&day) {
-            return Err(format!("incorrect day '{}', must be one of: {}", day, valid_days.join(" ")).into());
+    // swaps the backup and the current config, so restoring twice undoes the restore
+    pub fn restore_backup(config_path: &Path) -> Result<(), StudyTimerError> {
+        let backup_path = Self::backup_path(config_path);
+        if !backup_path.exists() {
+            return Err(StudyTimerError::BackupNotFound(backup_path));
         }
 
-        if !start_time.matches(|c| c == ':').count() == 1 {
-            return Err("Time must be in 'HH:MM' format".into());
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        let _lock = Self::lock(config_path, true)?;
 
-        let session = StudySession {
-            day: day.to_string(),
-            start_time: start_time.to_string(),
-            duration,
-        };
+        // the backup keeps the format of the config it was copied from
+        let backup_str = fs::read_to_string(&backup_path)?;
+        ConfigFormat::from_path(config_path).parse(&backup_str, &backup_path)?;
 
-        self.schedules
-            .entry(subject.to_string())
-            .or_insert_with(Vec::new)
-            .push(session);
+        if config_path.exists() {
+            let swap_path = Self::sibling_path(config_path, ".swap");
+            fs::rename(config_path, &swap_path)?;
+            fs::rename(&backup_path, config_path)?;
+            fs::rename(&swap_path, &backup_path)?;
+        } else {
+            fs::rename(&backup_path, config_path)?;
+        }
 
         Ok(())
     }
 
-    fn get_config_path() -> PathBuf {
-        if let Some(project_directories) = ProjectDirs::from("com", "study_timer", "study_timer") {
-            project_directories.config_dir().join("config.json")
-        } else {
-            PathBuf::from("./study_timer_config.json")
-        }
+    // restore with a report of what it changed, as lines like "subject 'DSA' re-added". only one level deep,
+    // undoing twice swaps the undone change back in. None when the current config can't be read to compare
+    pub fn undo(config_path: &Path) -> Result<Option<Vec<String>>, StudyTimerError> {
+        let current = Self::load_from(config_path).ok();
+        Self::restore_backup(config_path)?;
+        let restored = Self::load_from(config_path)?;
+
+        Ok(current.map(|current| Self::changes(&current, &restored)))
     }
-}
 
-#[cfg(test)]
-mod tests{
-    use super::*;
-    use std::io::Read;
-    use tempfile::tempdir;
+    // what going from `from` to `to` puts back or takes away, worded for undo
+    pub fn changes(from: &Config, to: &Config) -> Vec<String> {
+        let mut changes = Vec::new();
 
-    fn create_test_config() -> Config {
-        let temp_dir = tempdir().expect("failed tp create temp directory");
-        let config_path = temp_dir.path().join("test_config.json");
+        let names: BTreeSet<&String> = from.subjects.keys().chain(to.subjects.keys()).collect();
+        for name in &names {
+            match (from.subjects.get(*name), to.subjects.get(*name)) {
+                (None, Some(_)) => changes.push(format!("subject '{}' re-added", name)),
+                (Some(_), None) => changes.push(format!("subject '{}' removed", name)),
+                (Some(before), Some(after)) => {
+                    if before.target_hours != after.target_hours {
+                        changes.push(format!("target for '{}' back to {} hours", name, after.target_hours));
+                    }
+                    if before.completed_hours != after.completed_hours {
+                        changes.push(format!("completed hours for '{}' back to {}", name, after.completed_hours));
+                    }
+                    if before.deadline != after.deadline {
+                        changes.push(match after.deadline {
+                            Some(deadline) => format!("deadline for '{}' back to {}", name, deadline),
+                            None => format!("deadline for '{}' removed", name),
+                        });
+                    }
+                    if before.weekly_target != after.weekly_target {
+                        changes.push(match after.weekly_target {
+                            Some(weekly_target) => format!("weekly target for '{}' back to {} hours", name, weekly_target),
+                            None => format!("weekly target for '{}' removed", name),
+                        });
+                    }
+                    if before.tags != after.tags {
+                        changes.push(format!("tags for '{}' back to {}", name, if after.tags.is_empty() { "none".to_string() } else { after.tags.join(", ") }));
+                    }
+                },
+                (None, None) => {},
+            }
+        }
+
+        let describe = |session: &StudySession| format!("on {} at {} for {} minutes", session.describe_day(), session.start_time, session.duration);
+        let names: BTreeSet<&String> = from.schedules.keys().chain(to.schedules.keys()).collect();
+        for name in names {
+            let before: Vec<String> = from.schedules.get(name).into_iter().flatten().map(describe).collect();
+            let after: Vec<String> = to.schedules.get(name).into_iter().flatten().map(describe).collect();
+
+            for session in after.iter().filter(|session| !before.contains(session)) {
+                changes.push(format!("'{}' session {} re-added", name, session));
+            }
+            for session in before.iter().filter(|session| !after.contains(session)) {
+                changes.push(format!("'{}' session {} removed", name, session));
+            }
+        }
 
-        let mut config = Config::default();
-        config.config_path = config_path;
+        // everything else is a setting or bookkeeping, compared field by field
+        let settings = |config: &Config| match serde_json::to_value(config) {
+            Ok(serde_json::Value::Object(mut fields)) => {
+                for skipped in ["subjects", "schedules", "config_path"] {
+                    fields.remove(skipped);
+                }
+                fields
+            },
+            _ => serde_json::Map::new(),
+        };
+        let (before, after) = (settings(from), settings(to));
+        for (key, value) in &after {
+            if before.get(key) != Some(value) {
+                let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                changes.push(format!("{} back to {}", key.replace('_', "-"), value));
+            }
+        }
 
-        config
+        changes
     }
 
-    #[test]
-    fn test_default_config() {
-        let config = Config::default();
+    pub fn backup_path(config_path: &Path) -> PathBuf {
+        Self::sibling_path(config_path, ".bak")
+    }
 
-        assert!(config.subjects.is_empty());
-        assert!(config.schedules.is_empty());
-        assert!(!config.config_path.as_os_str().is_empty());
+    fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+        let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+        file_name.push(suffix);
+        path.with_file_name(file_name)
     }
 
-    #[test]
-    fn test_add_subject() {
-        let mut config = create_test_config();
-        let result = config.add_subject("dsa", 10.0);
+    // re-adding a subject resets its progress, so that needs an explicit force
+    // returns the name as stored, trimmed of surrounding whitespace
+    pub fn add_subject(&mut self, name: &str, target_hours: f32, completed_hours: f32, force: bool) -> Result<String, StudyTimerError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(StudyTimerError::InvalidSubject("subject name can not be empty".to_string()));
+        }
 
-        assert!(result.is_ok());
-        assert!(config.subjects.contains_key("dsa"));
-        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 10.0);
-        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 0.0);
+        Self::validate_target(target_hours)?;
 
-        let result = config.add_subject("ml/ai", 0.0);
-        assert!(result.is_err());
-        assert!(!config.subjects.contains_key("ml/ai"));
+        if completed_hours < 0.0 || !completed_hours.is_finite() {
+            return Err(StudyTimerError::InvalidHours(format!("completed hours must be a non-negative number, got {}", completed_hours)));
+        }
 
-        let result = config.add_subject("poetry", -5.0);
-        assert!(result.is_err());
-        assert!(!config.subjects.contains_key("poetry"));
+        // "DSA" and "dsa" are the same subject
+        if let Ok(existing) = self.resolve_subject(name).map(str::to_string) {
+            if !force {
+                return Err(StudyTimerError::DuplicateSubject(existing));
+            }
+
+            self.subjects.remove(&existing);
+            if let Some(sessions) = self.schedules.remove(&existing) {
+                self.schedules.insert(name.to_string(), sessions);
+            }
+        }
+
+        self.subjects.insert(name.to_string(), Subject {
+            target_hours,
+            completed_hours,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        });
+
+        Ok(name.to_string())
     }
 
-    #[test]
-    fn test_add_schedule() {
-        let mut config = create_test_config();
+    // finds the stored subject for a name that may differ in case or surrounding whitespace
+    pub fn resolve_subject(&self, name: &str) -> Result<&str, StudyTimerError> {
+        let name = name.trim();
 
-        config.add_subject("QA", 10.0).unwrap();
+        self.subjects
+            .get_key_value(name)
+            .or_else(|| self.subjects.iter().find(|(existing, _)| existing.to_lowercase() == name.to_lowercase()))
+            .map(|(existing, _)| existing.as_str())
+            .ok_or_else(|| StudyTimerError::SubjectNotFound(name.to_string()))
+    }
 
-        let result = config.add_schedule("QA", "Monday", "09:00", 60);
-        assert!(result.is_ok());
+    fn resolve_subject_mut(&mut self, name: &str) -> Result<&mut Subject, StudyTimerError> {
+        let name = self.resolve_subject(name)?.to_string();
+        self.subjects.get_mut(&name).ok_or(StudyTimerError::SubjectNotFound(name))
+    }
 
-        let qa_schedules = config.schedules.get("QA").unwrap();
-        assert_eq!(qa_schedules.len(), 1);
-        assert_eq!(qa_schedules[0].day, "Monday");
-        assert_eq!(qa_schedules[0].start_time, "09:00");
-        assert_eq!(qa_schedules[0].duration, 60);
+    pub fn add_schedule(&mut self, subject: &str, day: &str, start_time: &str, duration: u32) -> Result<(), StudyTimerError> {
+        let subject = &self.resolve_subject(subject)?.to_string();
 
-        let result = config.add_schedule("embedded", "Monday", "10:00", 30);
-        assert!(result.is_err());
-        assert!(!config.schedules.contains_key("embedded"));
+        let days = Self::parse_days(day)?;
 
-        let result = config.add_schedule("BE", "ijumaa", "10:00", 30);
-        assert!(result.is_err());
+        let start_time = Self::validate_session(start_time, duration)?;
 
-        let qa_schedules = config.schedules.get("QA").unwrap();
-        assert_eq!(qa_schedules.len(), 1);
+        let new_sessions: Vec<StudySession> = days
+            .into_iter()
+            .map(|day| StudySession::new(day, &start_time, duration))
+            .collect();
 
-        config.add_schedule("QA", "Tuesday", "14:00", 45).unwrap();
+        self.insert_sessions(subject, new_sessions)
+    }
 
-        let qa_schedules = config.schedules.get("QA").unwrap();
-        assert_eq!(qa_schedules.len(), 2);
-        assert_eq!(qa_schedules[1].day, "Tuesday");
+    // the day is asked for alongside the date to catch typos in either
+    pub fn add_one_time(&mut self, subject: &str, day: &str, date: NaiveDate, start_time: &str, duration: u32, today: NaiveDate) -> Result<(), StudyTimerError> {
+        let subject = &self.resolve_subject(subject)?.to_string();
+
+        let start_time = Self::validate_session(start_time, duration)?;
+
+        let session = StudySession::once(date, &start_time, duration);
+        if Self::parse_days(day)? != [session.day.as_str()] {
+            return Err(StudyTimerError::InvalidDay(format!("{} is a {}, not {}", date, session.day, day)));
+        }
+
+        if date < today {
+            return Err(StudyTimerError::InvalidDate(format!("{} has already passed", date)));
+        }
+
+        self.insert_sessions(subject, vec![session])
     }
 
-    #[test]
-    fn test_save_and_load() {
-        let mut config = create_test_config();
+    // drops one-off sessions that have ended, returns how many went
+    pub fn remove_expired_sessions(&mut self, now: NaiveDateTime) -> usize {
+        let mut removed = 0;
 
-        config.add_subject("DB", 10.0).unwrap();
-        config.add_schedule("DB", "Monday", "09:00", 50).unwrap();
+        self.schedules.retain(|_, sessions| {
+            let before = sessions.len();
+            sessions.retain(|session| !session.expired(now));
+            removed += before - sessions.len();
+            !sessions.is_empty()
+        });
 
-        let save_result = config.save();
-        assert!(save_result.is_ok());
-        assert!(config.config_path.exists());
+        removed
+    }
 
-        let mut file = File::open(&config.config_path).unwrap();
-        let mut content = String::new();
-        file.read_to_string(&mut content).unwrap();
+    // returns the start time in the canonical HH:MM form the daemon compares against
+    fn validate_session(start_time: &str, duration: u32) -> Result<String, StudyTimerError> {
+        let start_time = Self::parse_time(start_time)?;
 
-        assert!(content.contains("DB"));
-        assert!(content.contains("Monday"));
-        assert!(content.contains("09:00"));
+        if duration == 0 {
+            return Err(StudyTimerError::InvalidDuration("session duration must be at least 1 minute".to_string()));
+        }
 
-        let config_path = config.config_path.clone();
+        if duration > MAX_SESSION_MINUTES {
+            return Err(StudyTimerError::InvalidDuration(format!("session duration of {} minutes is too long, must be at most {} minutes", duration, MAX_SESSION_MINUTES)));
+        }
 
-        let mut new_config = Config::default();
-        new_config.config_path = config_path;
-        new_config.save().unwrap();
+        Ok(start_time.format("%H:%M").to_string())
+    }
 
-        let loaded_config = Config::load().unwrap();
+    pub fn validate_lead(minutes: u32) -> Result<(), StudyTimerError> {
+        if minutes > MAX_SESSION_MINUTES {
+            return Err(StudyTimerError::InvalidSetting(format!("reminder lead of {} minutes is too long, must be at most {} minutes", minutes, MAX_SESSION_MINUTES)));
+        }
 
-        assert!(loaded_config.subjects.contains_key("DB"));
-        assert_eq!(loaded_config.subjects.get("DB").unwrap().target_hours, 10.0);
-        assert!(loaded_config.schedules.contains_key("DB"));
+        Ok(())
+    }
 
-        let loaded_schedules = loaded_config.schedules.get("DB").unwrap();
-        assert_eq!(loaded_schedules[0].day, "Monday");
-        assert_eq!(loaded_schedules[0].start_time, "09:00");
+    // sessions from index `from` on get the lead, used right after adding them
+    pub fn set_lead(&mut self, subject: &str, from: usize, lead_minutes: Option<u32>) {
+        if let Some(sessions) = self.schedules.get_mut(subject) {
+            for session in sessions.iter_mut().skip(from) {
+                session.lead_minutes = lead_minutes;
+            }
+        }
     }
 
-    #[test]
-    fn test_load_nonexistent_config() {
-        let temp_dir = tempdir().expect("failed to create temporary directory");
-        let non_existent_path = temp_dir.path().join("404.json");
+    fn insert_sessions(&mut self, subject: &str, new_sessions: Vec<StudySession>) -> Result<(), StudyTimerError> {
+        if let Some(existing) = self.schedules.get(subject) {
+            for session in &new_sessions {
+                if let Some(clash) = existing.iter().find(|existing| existing.overlaps(session)) {
+                    return Err(StudyTimerError::ScheduleOverlap(format!("session on {} at {} for {} minutes overlaps the existing '{}' session on {} at {} for {} minutes",
+                                                                         session.describe_day(), session.start_time, session.duration,
+                                                                         subject, clash.describe_day(), clash.start_time, clash.duration)));
+                }
+            }
+        }
 
-        let mut config = Config::default();
-        config.config_path = non_existent_path;
+        self.schedules
+            .entry(subject.to_string())
+            .or_default()
+            .extend(new_sessions);
 
-        let result = Config::load();
-        assert!(result.is_err());
+        Ok(())
     }
 
-    #[test]
-    fn test_invalid_time_format() {
-        let mut config = create_test_config();
-        config.add_subject("OS", 10.0).unwrap();
+    // drops the subject's schedule entry once its last session is gone
+    pub fn remove_schedule(&mut self, subject: &str, day: &str, start_time: &str) -> Result<StudySession, StudyTimerError> {
+        let subject = &self.resolve_subject(subject)?.to_string();
 
-        let result = config.add_schedule("OS", "Wednesday", "1000", 60);
-        assert!(result.is_err());
+        let time = Self::parse_time(start_time)?;
+        let not_found = || StudyTimerError::ScheduleNotFound(format!("no '{}' session on {} at {}", subject, day, start_time));
 
-        let result = config.add_schedule("OS", "Wednesday", "10:00:00", 60);
-        assert!(result.is_err());
+        let sessions = self.schedules.get_mut(subject).ok_or_else(not_found)?;
+        let index = sessions
+            .iter()
+            .position(|session| session.day == day && Self::parse_time(&session.start_time).ok() == Some(time))
+            .ok_or_else(not_found)?;
+
+        let removed = sessions.remove(index);
+        if sessions.is_empty() {
+            self.schedules.remove(subject);
+        }
+
+        Ok(removed)
+    }
+
+    // the session keeps its duration. a one-off session keeps its date, so only its time can change.
+    // returns the session as it was and as it is now
+    pub fn move_schedule(&mut self, subject: &str, day: &str, start_time: &str, new_day: &str, new_start_time: &str) -> Result<(StudySession, StudySession), StudyTimerError> {
+        let subject = self.resolve_subject(subject)?.to_string();
+
+        let time = Self::parse_time(start_time)?;
+        let new_day = match Self::parse_days(new_day)?.as_slice() {
+            [day] => *day,
+            _ => return Err(StudyTimerError::InvalidDay(format!("can only move a session to a single day, not '{}'", new_day))),
+        };
+
+        let not_found = || StudyTimerError::ScheduleNotFound(format!("no '{}' session on {} at {}", subject, day, start_time));
+        let sessions = self.schedules.get_mut(&subject).ok_or_else(not_found)?;
+        let index = sessions
+            .iter()
+            .position(|session| session.day == day && Self::parse_time(&session.start_time).ok() == Some(time))
+            .ok_or_else(not_found)?;
+        let old = sessions.remove(index);
+
+        let moved = Self::validate_session(new_start_time, old.duration).and_then(|new_start_time| match old.date {
+            Some(_) if old.day != new_day => Err(StudyTimerError::InvalidDay(format!("the one-off session is on {}, only its time can be moved", old.describe_day()))),
+            Some(date) => Ok(StudySession { lead_minutes: old.lead_minutes, ..StudySession::once(date, &new_start_time, old.duration) }),
+            None => Ok(StudySession { lead_minutes: old.lead_minutes, ..StudySession::new(new_day, &new_start_time, old.duration) }),
+        });
+
+        // the session itself isn't in the way of its new slot, so it is only put back when the move fails
+        match moved.and_then(|moved| self.insert_sessions(&subject, vec![moved.clone()]).map(|_| moved)) {
+            Ok(moved) => Ok((old, moved)),
+            Err(e) => {
+                self.schedules.entry(subject).or_default().insert(index, old);
+                Err(e)
+            },
+        }
+    }
+
+    // copies the weekly sessions on from_day to each of to_days, one that would overlap a session
+    // already there is skipped rather than failing the whole copy. returns (copied, skipped)
+    pub fn copy_schedule(&mut self, subject: &str, from_day: &str, to_days: &str) -> Result<(Vec<StudySession>, Vec<StudySession>), StudyTimerError> {
+        let subject = self.resolve_subject(subject)?.to_string();
+
+        let from_day = match Self::parse_days(from_day)?.as_slice() {
+            [day] => *day,
+            _ => return Err(StudyTimerError::InvalidDay(format!("can only copy from a single day, not '{}'", from_day))),
+        };
+        let to_days = Self::parse_days(to_days)?;
+        if to_days.contains(&from_day) {
+            return Err(StudyTimerError::InvalidDay(format!("can not copy {} onto itself", from_day)));
+        }
+
+        let sources: Vec<StudySession> = self.schedules
+            .get(&subject)
+            .map(|sessions| sessions.iter().filter(|session| session.date.is_none() && session.day == from_day).cloned().collect())
+            .unwrap_or_default();
+        if sources.is_empty() {
+            return Err(StudyTimerError::ScheduleNotFound(format!("no weekly '{}' sessions on {}", subject, from_day)));
+        }
+
+        let (mut copied, mut skipped) = (Vec::new(), Vec::new());
+        for day in to_days {
+            for source in &sources {
+                let session = StudySession { lead_minutes: source.lead_minutes, ..StudySession::new(day, &source.start_time, source.duration) };
+                // checked one at a time so later copies also see the earlier ones
+                match self.insert_sessions(&subject, vec![session.clone()]) {
+                    Ok(()) => copied.push(session),
+                    Err(StudyTimerError::ScheduleOverlap(_)) => skipped.push(session),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        Ok((copied, skipped))
+    }
+
+    // returns the previous target, completed hours are left as they are
+    pub fn edit_target(&mut self, name: &str, target_hours: f32) -> Result<f32, StudyTimerError> {
+        Self::validate_target(target_hours)?;
+
+        let subject = self.resolve_subject_mut(name)?;
+
+        let old_target = subject.target_hours;
+        subject.target_hours = target_hours;
+
+        Ok(old_target)
+    }
+
+    // overwrites rather than adds, for correcting a wrong total. returns the previous value
+    pub fn set_completed_hours(&mut self, name: &str, hours: f32) -> Result<f32, StudyTimerError> {
+        if hours < 0.0 || !hours.is_finite() {
+            return Err(StudyTimerError::InvalidHours(format!("completed hours must be a non-negative number, got {}", hours)));
+        }
+
+        let subject = self.resolve_subject_mut(name)?;
+
+        let old_hours = subject.completed_hours;
+        subject.completed_hours = hours;
+
+        Ok(old_hours)
+    }
+
+    // trimmed, lowercased and deduplicated, in the order given
+    pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, StudyTimerError> {
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() {
+                return Err(StudyTimerError::InvalidTag("tags can not be empty".to_string()));
+            }
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+
+        Ok(normalized)
+    }
+
+    // replaces the subject's tags, they are trimmed, lowercased and deduplicated
+    pub fn set_tags(&mut self, name: &str, tags: &[String]) -> Result<(), StudyTimerError> {
+        let normalized = Self::normalize_tags(tags)?;
+
+        let subject = self.resolve_subject_mut(name)?;
+
+        subject.tags = normalized;
+
+        Ok(())
+    }
+
+    pub fn set_weekly_target(&mut self, name: &str, weekly_target: f32) -> Result<(), StudyTimerError> {
+        Self::validate_target(weekly_target)?;
+
+        let subject = self.resolve_subject_mut(name)?;
+
+        subject.weekly_target = Some(weekly_target);
+
+        Ok(())
+    }
+
+    pub fn week_start_of(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+        let days_into_week = (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+        date - chrono::Duration::days(days_into_week as i64)
+    }
+
+    pub fn current_week_start(&self, today: NaiveDate) -> NaiveDate {
+        Self::week_start_of(today, self.week_start)
+    }
+
+    // zeroes every subject's weekly hours once a new week has begun, returns whether it did.
+    // the reset date is saved with the config so a restart doesn't reset twice
+    pub fn reset_weekly(&mut self, today: NaiveDate) -> bool {
+        let week = self.current_week_start(today);
+        if self.last_weekly_reset == Some(week) {
+            return false;
+        }
+
+        for subject in self.subjects.values_mut() {
+            subject.weekly_completed = 0.0;
+        }
+        self.last_weekly_reset = Some(week);
+
+        true
+    }
+
+    // counts as nothing yet when the week has rolled over but no reset has run
+    pub fn weekly_completed(&self, subject: &Subject, today: NaiveDate) -> f32 {
+        if self.last_weekly_reset == Some(self.current_week_start(today)) {
+            subject.weekly_completed
+        } else {
+            0.0
+        }
+    }
+
+    pub fn set_deadline(&mut self, name: &str, deadline: Option<NaiveDate>) -> Result<(), StudyTimerError> {
+        let subject = self.resolve_subject_mut(name)?;
+
+        subject.deadline = deadline;
+
+        Ok(())
+    }
+
+    // the new name follows the same rules as add_subject, only the subject itself may differ from it by case
+    pub fn rename_subject(&mut self, old: &str, new: &str) -> Result<(), StudyTimerError> {
+        let old = self.resolve_subject(old)?.to_string();
+
+        let new = new.trim();
+        if new.is_empty() {
+            return Err(StudyTimerError::InvalidSubject("subject name can not be empty".to_string()));
+        }
+
+        if let Ok(existing) = self.resolve_subject(new) && existing != old {
+            return Err(StudyTimerError::DuplicateSubject(existing.to_string()));
+        }
+
+        if let Some(subject) = self.subjects.remove(&old) {
+            self.subjects.insert(new.to_string(), subject);
+        }
+
+        if let Some(sessions) = self.schedules.remove(&old) {
+            self.schedules.insert(new.to_string(), sessions);
+        }
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self, until: Option<NaiveDate>, today: NaiveDate) -> Result<(), StudyTimerError> {
+        if let Some(until) = until && until <= today {
+            return Err(StudyTimerError::InvalidDate(format!("pause date {} must be after today", until)));
+        }
+
+        self.paused = true;
+        self.paused_until = until;
+
+        Ok(())
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.paused_until = None;
+    }
+
+    // reminders come back on the paused_until date itself
+    pub fn is_paused(&self, today: NaiveDate) -> bool {
+        self.paused && self.paused_until.is_none_or(|until| today < until)
+    }
+
+    // a machine that travels or changes its zone keeps reminding on the configured zone's clock
+    pub fn wall_clock<Z: TimeZone>(&self, now: &DateTime<Z>) -> NaiveDateTime {
+        match self.timezone {
+            Some(timezone) => now.with_timezone(&timezone).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
+        }
+    }
+
+    // the reverse of wall_clock, None for a time skipped by a DST change
+    pub fn local_from_wall_clock(&self, at: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self.timezone {
+            Some(timezone) => timezone.from_local_datetime(&at).earliest().map(|at| at.with_timezone(&Local)),
+            None => Local.from_local_datetime(&at).earliest(),
+        }
+    }
+
+    pub fn dnd_window(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let start = Self::parse_time(self.dnd_start.as_deref()?).ok()?;
+        let end = Self::parse_time(self.dnd_end.as_deref()?).ok()?;
+
+        Some((start, end))
+    }
+
+    // every {...} has to name a known placeholder, a typo would otherwise show up verbatim in every reminder
+    fn validate_template(template: &str) -> Result<(), StudyTimerError> {
+        if template.trim().is_empty() {
+            return Err(StudyTimerError::InvalidSetting("notification template can not be empty".to_string()));
+        }
+
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..].find('}')
+                .ok_or_else(|| StudyTimerError::InvalidSetting(format!("unclosed '{{' in template '{}'", template)))?;
+            let name = &rest[open + 1..open + close];
+
+            if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+                let known: Vec<String> = TEMPLATE_PLACEHOLDERS.iter().map(|name| format!("{{{}}}", name)).collect();
+                return Err(StudyTimerError::InvalidSetting(format!("unknown placeholder '{{{}}}' in template, must be one of: {}", name, known.join(" "))));
+            }
+
+            rest = &rest[open + close + 1..];
+        }
+
+        Ok(())
+    }
+
+    // one pass over the template, so a subject that itself looks like a placeholder is left alone
+    pub fn render_template(template: &str, subject: &str, session: &StudySession, lead_minutes: u32) -> String {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            rendered.push_str(&rest[..open]);
+            let tail = &rest[open..];
+            let placeholder = tail.find('}').map_or(tail, |close| &tail[..=close]);
+            let value = match placeholder {
+                "{subject}" => subject.to_string(),
+                "{duration}" => session.duration.to_string(),
+                "{time}" => session.start_time.clone(),
+                "{lead}" => lead_minutes.to_string(),
+                _ => {
+                    rendered.push('{');
+                    rest = &tail[1..];
+                    continue;
+                }
+            };
+            rendered.push_str(&value);
+            rest = &tail[placeholder.len()..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    // read-only, finds what a hand edit could have broken. one line per problem, subjects in name order
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let mut subjects: Vec<(&String, &Subject)> = self.subjects.iter().collect();
+        subjects.sort_by_key(|(name, _)| *name);
+        for (name, subject) in subjects {
+            if subject.target_hours <= 0.0 || !subject.target_hours.is_finite() {
+                problems.push(format!("subject '{}' has a target of {} hours, it must be more than 0", name, subject.target_hours));
+            }
+            if subject.completed_hours < 0.0 || !subject.completed_hours.is_finite() {
+                problems.push(format!("subject '{}' has {} completed hours, it can not be negative", name, subject.completed_hours));
+            } else if subject.completed_hours > subject.target_hours {
+                problems.push(format!("subject '{}' has {} completed hours, more than its target of {}", name, subject.completed_hours, subject.target_hours));
+            }
+        }
+
+        let mut schedules: Vec<(&String, &Vec<StudySession>)> = self.schedules.iter().collect();
+        schedules.sort_by_key(|(name, _)| *name);
+        for (name, sessions) in &schedules {
+            if !self.subjects.contains_key(*name) {
+                problems.push(format!("schedule for '{}' has {} session(s) but there is no such subject", name, sessions.len()));
+            }
+
+            for session in sessions.iter() {
+                let described = format!("'{}' session on {} at {}", name, session.describe_day(), session.start_time);
+
+                if !VALID_DAYS.contains(&session.day.as_str()) {
+                    problems.push(format!("{} has an unknown day '{}'", described, session.day));
+                }
+                // the daemon compares the stored text with the clock, so it has to be exactly HH:MM
+                match NaiveTime::parse_from_str(&session.start_time, "%H:%M") {
+                    Ok(time) if time.format("%H:%M").to_string() == session.start_time => {},
+                    _ => problems.push(format!("{} has a malformed start time, expected HH:MM", described)),
+                }
+                if session.duration == 0 || session.duration > MAX_SESSION_MINUTES {
+                    problems.push(format!("{} lasts {} minutes, it must be between 1 and {}", described, session.duration, MAX_SESSION_MINUTES));
+                }
+            }
+        }
+
+        let all_sessions: Vec<(&str, &StudySession)> = schedules
+            .iter()
+            .flat_map(|(name, sessions)| sessions.iter().map(move |session| (name.as_str(), session)))
+            .collect();
+        for (i, (name, session)) in all_sessions.iter().enumerate() {
+            for (other_name, other) in &all_sessions[i + 1..] {
+                if session.overlaps(other) {
+                    problems.push(format!("'{}' session on {} at {}-{} overlaps '{}' session on {} at {}-{}",
+                                          name, session.describe_day(), session.start_time, session.end_time,
+                                          other_name, other.describe_day(), other.start_time, other.end_time));
+                }
+            }
+        }
+
+        problems
+    }
+
+    // the window covers start up to but not including end, 22:00-07:00 wraps past midnight
+    pub fn in_dnd(&self, time: NaiveTime) -> bool {
+        match self.dnd_window() {
+            Some((start, end)) if start < end => time >= start && time < end,
+            Some((start, end)) => time >= start || time < end,
+            None => false,
+        }
+    }
+
+    pub fn overlaps_dnd(&self, start: NaiveTime, minutes: u32) -> bool {
+        (0..minutes as i64).any(|minute| self.in_dnd(start.overflowing_add_signed(chrono::Duration::minutes(minute)).0))
+    }
+
+    pub fn validate_target(target_hours: f32) -> Result<(), StudyTimerError> {
+        if target_hours <= 0.0 {
+            return Err(StudyTimerError::InvalidTarget("you must set a target time for yoyr study".to_string()));
+        }
+
+        // NaN fails every comparison and inf passes them, neither is a target
+        if !target_hours.is_finite() {
+            return Err(StudyTimerError::InvalidTarget(format!("target '{}' must be a finite number of hours", target_hours)));
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_subject(&mut self, name: &str) -> Result<usize, StudyTimerError> {
+        let name = self.resolve_subject(name)?.to_string();
+        self.subjects.remove(&name);
+
+        let removed_sessions = self.schedules
+            .remove(&name)
+            .map(|sessions| sessions.len())
+            .unwrap_or(0);
+
+        Ok(removed_sessions)
+    }
+
+    // settings are kept, only subjects and their sessions go. returns how many of each were removed
+    pub fn clear(&mut self) -> (usize, usize) {
+        let removed_subjects = self.subjects.len();
+        let removed_sessions = self.schedules.values().map(|sessions| sessions.len()).sum();
+
+        self.subjects.clear();
+        self.schedules.clear();
+
+        (removed_subjects, removed_sessions)
+    }
+
+    pub fn log_hours(&mut self, name: &str, hours: f32) -> Result<(), StudyTimerError> {
+        if hours < 0.0 || !hours.is_finite() {
+            return Err(StudyTimerError::InvalidHours("logged hours can not be negative".to_string()));
+        }
+
+        // hours from last week must not count towards this one
+        self.reset_weekly(Local::now().date_naive());
+
+        let subject = self.resolve_subject_mut(name)?;
+
+        subject.completed_hours += hours;
+        subject.weekly_completed += hours;
+        if hours > 0.0 {
+            let now = Local::now();
+            subject.last_studied = Some(now);
+            self.study_days.insert(now.date_naive());
+        }
+
+        Ok(())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), StudyTimerError> {
+        match key {
+            "reminder-lead-minutes" => {
+                let minutes = value.parse::<u32>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected a whole number of minutes", value, key)))?;
+
+                Self::validate_lead(minutes)?;
+                self.reminder_lead_minutes = minutes;
+            },
+            "notification-icon" => {
+                if value.trim().is_empty() {
+                    return Err(StudyTimerError::InvalidSetting("notification icon can not be empty".to_string()));
+                }
+
+                self.notification_icon = value.trim().to_string();
+            },
+            "notification-timeout-ms" => {
+                self.notification_timeout_ms = value.parse::<u32>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected a whole number of milliseconds", value, key)))?;
+            },
+            "notification-urgency" => {
+                self.notification_urgency = value.parse::<Urgency>().map_err(StudyTimerError::InvalidSetting)?;
+            },
+            "notification-backend" => {
+                self.notification_backend = value.parse::<Backend>().map_err(StudyTimerError::InvalidSetting)?;
+            },
+            "webhook-url" => {
+                let value = value.trim();
+                if value.eq_ignore_ascii_case("off") {
+                    self.webhook_url = None;
+                    return Ok(());
+                }
+
+                if !value.starts_with("https://") && !value.starts_with("http://") {
+                    return Err(StudyTimerError::InvalidSetting(format!("webhook url '{}' must start with https:// or http://, or be 'off'", value)));
+                }
+
+                self.webhook_url = Some(value.to_string());
+            },
+            "neglect-days" => {
+                self.neglect_days = value.parse::<u32>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected a whole number of days, 0 disables the reminder", value, key)))?;
+            },
+            "dnd" => {
+                if value.eq_ignore_ascii_case("off") {
+                    self.dnd_start = None;
+                    self.dnd_end = None;
+                    return Ok(());
+                }
+
+                let (start, end) = value.split_once('-')
+                    .ok_or_else(|| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected HH:MM-HH:MM or off", value, key)))?;
+                let (start, end) = (Self::parse_time(start.trim())?, Self::parse_time(end.trim())?);
+
+                if start == end {
+                    return Err(StudyTimerError::InvalidSetting("do-not-disturb start and end can not be the same time".to_string()));
+                }
+
+                self.dnd_start = Some(start.format("%H:%M").to_string());
+                self.dnd_end = Some(end.format("%H:%M").to_string());
+            },
+            "digest-time" => {
+                if value.eq_ignore_ascii_case("off") {
+                    self.digest_time = None;
+                    return Ok(());
+                }
+
+                self.digest_time = Some(Self::parse_time(value)?.format("%H:%M").to_string());
+            },
+            "digest-empty-days" => {
+                self.digest_empty_days = value.parse::<bool>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected true or false", value, key)))?;
+            },
+            "reminder-template" => {
+                Self::validate_template(value)?;
+                self.reminder_template = value.to_string();
+            },
+            "pre-reminder-template" => {
+                Self::validate_template(value)?;
+                self.pre_reminder_template = value.to_string();
+            },
+            "timezone" => {
+                let value = value.trim();
+                if value.eq_ignore_ascii_case("local") {
+                    self.timezone = None;
+                    return Ok(());
+                }
+
+                let timezone = value.parse::<Tz>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("unknown timezone '{}', expected an IANA name like Europe/Berlin, or local", value)))?;
+                self.timezone = Some(timezone);
+            },
+            "week-start" => {
+                let week_start = value.trim().parse::<Weekday>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected a day like Monday or Sunday", value, key)))?;
+
+                // keep this week's hours, they would otherwise read as a stale week under the new start
+                let today = Local::now().date_naive();
+                if self.last_weekly_reset == Some(Self::week_start_of(today, self.week_start)) {
+                    self.last_weekly_reset = Some(Self::week_start_of(today, week_start));
+                }
+                self.week_start = week_start;
+            },
+            "progress-colors" => {
+                self.progress_colors = value.parse::<bool>()
+                    .map_err(|_| StudyTimerError::InvalidSetting(format!("invalid value '{}' for '{}', expected true or false", value, key)))?;
+            },
+            _ => return Err(StudyTimerError::InvalidSetting(format!("unknown setting '{}', must be one of: {}", key, SETTINGS.join(" ")))),
+        }
+
+        Ok(())
+    }
+
+    // accepts a single day, a comma separated list of days, or the `weekdays`/`weekends` shorthands
+    pub fn parse_days(days: &str) -> Result<Vec<&'static str>, StudyTimerError> {
+        let mut parsed = Vec::new();
+
+        for day in days.split(',').map(str::trim) {
+            let expanded: &[&'static str] = match day.to_lowercase().as_str() {
+                "weekdays" => &VALID_DAYS[..5],
+                "weekends" => &VALID_DAYS[5..],
+                _ => match VALID_DAYS.iter().find(|valid| **valid == day) {
+                    Some(valid) => std::slice::from_ref(valid),
+                    None => return Err(StudyTimerError::InvalidDay(format!("incorrect day '{}', must be one of: {} (or weekdays, weekends)", day, VALID_DAYS.join(" ")))),
+                },
+            };
+
+            for day in expanded {
+                if !parsed.contains(day) {
+                    parsed.push(*day);
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    // 24-hour "14:00", or 12-hour "2:00 PM" and "2PM"
+    fn parse_time(time: &str) -> Result<NaiveTime, StudyTimerError> {
+        let upper = time.trim().to_uppercase();
+        if upper.ends_with("AM") || upper.ends_with("PM") {
+            return Self::parse_12_hour_time(&upper)
+                .ok_or_else(|| StudyTimerError::InvalidTime(format!("time '{}' must be like '2:00 PM' or '2PM', with the hour in 1-12", time)));
+        }
+
+        let (hours, minutes) = match time.split_once(':') {
+            Some(parts) => parts,
+            None => return Err(StudyTimerError::InvalidTime(format!("time '{}' must be in 'HH:MM' format", time))),
+        };
+
+        if let Ok(hours) = hours.parse::<u32>() && hours > 23 {
+            return Err(StudyTimerError::InvalidTime(format!("hour '{}' in '{}' is out of range, must be 00-23", hours, time)));
+        }
+
+        if let Ok(minutes) = minutes.parse::<u32>() && minutes > 59 {
+            return Err(StudyTimerError::InvalidTime(format!("minute '{}' in '{}' is out of range, must be 00-59", minutes, time)));
+        }
+
+        NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| StudyTimerError::InvalidTime(format!("time '{}' must be in 'HH:MM' format", time)))
+    }
+
+    // expects the uppercased input ending in AM or PM
+    fn parse_12_hour_time(time: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(time, "%I:%M %p")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%I:%M%p"))
+            .ok()
+            .or_else(|| {
+                // chrono won't build a time without minutes, so "2PM" is read as "2:00 PM"
+                let (hour, meridiem) = time.split_at(time.len() - 2);
+                let hour = hour.trim();
+                if hour.is_empty() || !hour.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                NaiveTime::parse_from_str(&format!("{}:00 {}", hour, meridiem), "%I:%M %p").ok()
+            })
+    }
+
+    pub fn get_config_path() -> PathBuf {
+        if let Some(project_directories) = ProjectDirs::from("com", "study_timer", "study_timer") {
+            project_directories.config_dir().join("config.json")
+        } else {
+            PathBuf::from("./study_timer_config.json")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    fn create_test_config() -> Config {
+        let temp_dir = tempdir().expect("failed tp create temp directory");
+        let config_path = temp_dir.path().join("test_config.json");
+
+        Config { config_path, ..Default::default() }
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+
+        assert!(config.subjects.is_empty());
+        assert!(config.schedules.is_empty());
+        assert!(!config.config_path.as_os_str().is_empty());
+    }
+
+    #[test]
+    fn test_add_subject() {
+        let mut config = create_test_config();
+        let result = config.add_subject("dsa", 10.0, 0.0, false);
+
+        assert!(result.is_ok());
+        assert!(config.subjects.contains_key("dsa"));
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 10.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 0.0);
+
+        let result = config.add_subject("ml/ai", 0.0, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+        assert!(!config.subjects.contains_key("ml/ai"));
+
+        let result = config.add_subject("poetry", -5.0, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+        assert!(!config.subjects.contains_key("poetry"));
+
+        let result = config.add_subject("poetry", f32::NAN, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+        let result = config.add_subject("poetry", f32::INFINITY, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+        assert!(!config.subjects.contains_key("poetry"));
+    }
+
+    #[test]
+    fn test_edit_target() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.log_hours("dsa", 4.0).unwrap();
+
+        let old_target = config.edit_target("dsa", 25.0).unwrap();
+        assert_eq!(old_target, 10.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 25.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 4.0);
+
+        let result = config.edit_target("dsa", 0.0);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 25.0);
+
+        assert!(matches!(config.edit_target("dsa", f32::NAN), Err(StudyTimerError::InvalidTarget(_))));
+        assert!(matches!(config.edit_target("dsa", f32::INFINITY), Err(StudyTimerError::InvalidTarget(_))));
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 25.0);
+
+        let result = config.edit_target("ml/ai", 5.0);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_rename_subject() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.log_hours("dsa", 4.0).unwrap();
+        config.add_schedule("dsa", "Monday,Friday", "10:00", 60).unwrap();
+
+        config.rename_subject("dsa", "algorithms").unwrap();
+        assert!(!config.subjects.contains_key("dsa"));
+        assert!(!config.schedules.contains_key("dsa"));
+
+        let subject = config.subjects.get("algorithms").unwrap();
+        assert_eq!(subject.target_hours, 10.0);
+        assert_eq!(subject.completed_hours, 4.0);
+        assert_eq!(config.schedules.get("algorithms").unwrap().len(), 2);
+
+        let result = config.rename_subject("dsa", "data structures");
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+
+        let result = config.rename_subject("algorithms", "OS");
+        assert!(matches!(result, Err(StudyTimerError::DuplicateSubject(_))));
+        assert!(config.schedules.contains_key("algorithms"));
+
+        assert!(matches!(config.rename_subject("algorithms", " os "), Err(StudyTimerError::DuplicateSubject(_))));
+        assert!(matches!(config.rename_subject("algorithms", "  "), Err(StudyTimerError::InvalidSubject(_))));
+
+        // only the case of its own name changes
+        config.rename_subject(" ALGORITHMS", "Algorithms ").unwrap();
+        assert!(config.subjects.contains_key("Algorithms"));
+        assert_eq!(config.schedules.get("Algorithms").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_subject_names_resolved() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.add_schedule("DSA", "Monday", "10:00", 60).unwrap();
+
+        config.log_hours(" DSA", 1.0).unwrap();
+        config.edit_target("Dsa", 20.0).unwrap();
+        config.set_completed_hours("DSA", 2.0).unwrap();
+        config.set_tags("DSA", &["algo".to_string()]).unwrap();
+        config.set_weekly_target("DSA", 4.0).unwrap();
+        config.set_deadline("DSA", NaiveDate::from_ymd_opt(2030, 1, 1)).unwrap();
+
+        let subject = config.subjects.get("dsa").unwrap();
+        assert_eq!((subject.target_hours, subject.completed_hours, subject.weekly_target), (20.0, 2.0, Some(4.0)));
+        assert_eq!(subject.tags, vec!["algo".to_string()]);
+        assert!(subject.deadline.is_some());
+
+        config.remove_schedule("DSA", "Monday", "10:00").unwrap();
+        assert!(!config.schedules.contains_key("dsa"));
+        assert_eq!(config.remove_subject("DSA").unwrap(), 0);
+        assert!(config.subjects.is_empty());
+    }
+
+    #[test]
+    fn test_move_schedule() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.add_schedule("dsa", "Tuesday", "09:00", 60).unwrap();
+        config.add_schedule("dsa", "Thursday", "14:00", 60).unwrap();
+
+        let (old, moved) = config.move_schedule("DSA", "Tuesday", "9:00", "Wednesday", "10:30").unwrap();
+        assert_eq!((old.day.as_str(), old.start_time.as_str()), ("Tuesday", "09:00"));
+        assert_eq!((moved.day.as_str(), moved.start_time.as_str(), moved.end_time.as_str(), moved.duration), ("Wednesday", "10:30", "11:30", 60));
+        assert_eq!(config.schedules.get("dsa").unwrap().len(), 2);
+
+        // later the same day overlaps only the session being moved
+        config.move_schedule("dsa", "Wednesday", "10:30", "Wednesday", "11:00").unwrap();
+
+        let result = config.move_schedule("dsa", "Wednesday", "11:00", "Thursday", "14:30");
+        assert!(matches!(result, Err(StudyTimerError::ScheduleOverlap(_))));
+        let result = config.move_schedule("dsa", "Wednesday", "11:00", "Thursday", "25:00");
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+        let sessions = config.schedules.get("dsa").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().any(|session| session.day == "Wednesday" && session.start_time == "11:00"));
+
+        assert!(matches!(config.move_schedule("dsa", "Monday", "09:00", "Friday", "09:00"), Err(StudyTimerError::ScheduleNotFound(_))));
+        assert!(matches!(config.move_schedule("dsa", "Thursday", "14:00", "weekends", "09:00"), Err(StudyTimerError::InvalidDay(_))));
+        assert!(matches!(config.move_schedule("OS", "Thursday", "14:00", "Friday", "09:00"), Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_copy_schedule() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.add_schedule("dsa", "Tuesday", "09:00", 60).unwrap();
+        config.add_schedule("dsa", "Tuesday", "14:00", 90).unwrap();
+        config.add_schedule("dsa", "Thursday", "14:30", 30).unwrap();
+
+        let (copied, skipped) = config.copy_schedule("DSA", "Tuesday", "Thursday").unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!((copied[0].day.as_str(), copied[0].start_time.as_str(), copied[0].duration), ("Thursday", "09:00", 60));
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].start_time, "14:00");
+        assert_eq!(config.schedules.get("dsa").unwrap().len(), 4);
+
+        // copying again only finds duplicates
+        let (copied, skipped) = config.copy_schedule("dsa", "Tuesday", "Thursday").unwrap();
+        assert!(copied.is_empty());
+        assert_eq!(skipped.len(), 2);
+
+        let (copied, _) = config.copy_schedule("dsa", "Tuesday", "Saturday,Sunday").unwrap();
+        assert_eq!(copied.len(), 4);
+
+        assert!(matches!(config.copy_schedule("dsa", "Monday", "Friday"), Err(StudyTimerError::ScheduleNotFound(_))));
+        assert!(matches!(config.copy_schedule("dsa", "Tuesday", "Tuesday"), Err(StudyTimerError::InvalidDay(_))));
+        assert!(matches!(config.copy_schedule("dsa", "weekdays", "Sunday"), Err(StudyTimerError::InvalidDay(_))));
+        assert!(matches!(config.copy_schedule("dsa", "Tuesday", "Thursdy"), Err(StudyTimerError::InvalidDay(_))));
+        assert!(matches!(config.copy_schedule("OS", "Tuesday", "Thursday"), Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_remove_schedule() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.add_schedule("dsa", "Monday,Friday", "09:00", 60).unwrap();
+
+        let removed = config.remove_schedule("dsa", "Monday", "9:00").unwrap();
+        assert_eq!(removed.day, "Monday");
+        assert_eq!(removed.start_time, "09:00");
+
+        let sessions = config.schedules.get("dsa").unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].day, "Friday");
+
+        let result = config.remove_schedule("dsa", "Monday", "09:00");
+        assert!(matches!(result, Err(StudyTimerError::ScheduleNotFound(_))));
+
+        let result = config.remove_schedule("dsa", "Friday", "10:00");
+        assert!(matches!(result, Err(StudyTimerError::ScheduleNotFound(_))));
+
+        let result = config.remove_schedule("OS", "Friday", "09:00");
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+
+        config.remove_schedule("dsa", "Friday", "09:00").unwrap();
+        assert!(!config.schedules.contains_key("dsa"));
+        assert!(config.subjects.contains_key("dsa"));
+
+        let result = config.remove_schedule("dsa", "Friday", "09:00");
+        assert!(matches!(result, Err(StudyTimerError::ScheduleNotFound(_))));
+    }
+
+    #[test]
+    fn test_add_duplicate_subject() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.log_hours("dsa", 4.0).unwrap();
+
+        let result = config.add_subject("dsa", 20.0, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::DuplicateSubject(_))));
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 10.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 4.0);
+
+        config.add_subject("dsa", 20.0, 0.0, true).unwrap();
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 20.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 0.0);
+    }
+
+    #[test]
+    fn test_add_subject_with_completed_hours() {
+        let mut config = create_test_config();
+
+        config.add_subject("dsa", 50.0, 20.0, false).unwrap();
+        let subject = config.subjects.get("dsa").unwrap();
+        assert_eq!(subject.completed_hours, 20.0);
+        // hours from before tracking started aren't a study day
+        assert!(subject.last_studied.is_none());
+        assert!(config.study_days.is_empty());
+
+        for invalid in [-1.0, f32::NAN] {
+            let result = config.add_subject("os", 10.0, invalid, false);
+            assert!(matches!(result, Err(StudyTimerError::InvalidHours(_))));
+        }
+        assert!(!config.subjects.contains_key("os"));
+    }
+
+    #[test]
+    fn test_add_subject_normalizes_name() {
+        let mut config = create_test_config();
+
+        assert_eq!(config.add_subject("  DSA ", 10.0, 0.0, false).unwrap(), "DSA");
+        assert!(config.subjects.contains_key("DSA"));
+
+        for empty in ["", "   "] {
+            let result = config.add_subject(empty, 10.0, 0.0, false);
+            assert!(matches!(result, Err(StudyTimerError::InvalidSubject(_))));
+        }
+
+        let result = config.add_subject("dsa", 20.0, 0.0, false);
+        assert!(matches!(result, Err(StudyTimerError::DuplicateSubject(ref name)) if name == "DSA"));
+        assert_eq!(config.subjects.len(), 1);
+
+        // a schedule for "dsa" lands on the stored "DSA"
+        config.add_schedule(" dsa", "Monday", "09:00", 60).unwrap();
+        assert_eq!(config.schedules.get("DSA").unwrap().len(), 1);
+        assert!(!config.schedules.contains_key("dsa"));
+
+        // overwriting under a new spelling keeps the schedule with the subject
+        config.add_subject("dsa", 20.0, 0.0, true).unwrap();
+        assert!(!config.subjects.contains_key("DSA"));
+        assert_eq!(config.subjects.get("dsa").unwrap().target_hours, 20.0);
+        assert_eq!(config.schedules.get("dsa").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_add_schedule() {
+        let mut config = create_test_config();
+
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 60);
+        assert!(result.is_ok());
+
+        let qa_schedules = config.schedules.get("QA").unwrap();
+        assert_eq!(qa_schedules.len(), 1);
+        assert_eq!(qa_schedules[0].day, "Monday");
+        assert_eq!(qa_schedules[0].start_time, "09:00");
+        assert_eq!(qa_schedules[0].duration, 60);
+        assert_eq!(qa_schedules[0].end_time, "10:00");
+
+        let result = config.add_schedule("embedded", "Monday", "10:00", 30);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+        assert!(!config.schedules.contains_key("embedded"));
+
+        let result = config.add_schedule("BE", "ijumaa", "10:00", 30);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+
+        let result = config.add_schedule("QA", "ijumaa", "10:00", 30);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDay(_))));
+
+        let qa_schedules = config.schedules.get("QA").unwrap();
+        assert_eq!(qa_schedules.len(), 1);
+
+        config.add_schedule("QA", "Tuesday", "14:00", 45).unwrap();
+
+        let qa_schedules = config.schedules.get("QA").unwrap();
+        assert_eq!(qa_schedules.len(), 2);
+        assert_eq!(qa_schedules[1].day, "Tuesday");
+    }
+
+    #[test]
+    fn test_add_schedule_multiple_days() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        config.add_schedule("QA", "Monday,Wednesday, Friday", "09:00", 60).unwrap();
+        let days: Vec<&str> = config.schedules.get("QA").unwrap().iter().map(|s| s.day.as_str()).collect();
+        assert_eq!(days, vec!["Monday", "Wednesday", "Friday"]);
+
+        let result = config.add_schedule("QA", "Tuesday,Funday", "18:00", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDay(_))));
+        assert_eq!(config.schedules.get("QA").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_add_schedule_day_shorthands() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_subject("OS", 10.0, 0.0, false).unwrap();
+
+        config.add_schedule("QA", "weekdays", "09:00", 60).unwrap();
+        let days: Vec<&str> = config.schedules.get("QA").unwrap().iter().map(|s| s.day.as_str()).collect();
+        assert_eq!(days, vec!["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"]);
+
+        config.add_schedule("OS", "weekends", "10:00", 60).unwrap();
+        let days: Vec<&str> = config.schedules.get("OS").unwrap().iter().map(|s| s.day.as_str()).collect();
+        assert_eq!(days, vec!["Saturday", "Sunday"]);
+    }
+
+    #[test]
+    fn test_add_schedule_exact_overlap() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 60);
+        assert!(matches!(result, Err(StudyTimerError::ScheduleOverlap(_))));
+        assert_eq!(config.schedules.get("QA").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_add_schedule_partial_overlap() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+
+        assert!(matches!(config.add_schedule("QA", "Monday", "09:30", 60), Err(StudyTimerError::ScheduleOverlap(_))));
+        assert!(matches!(config.add_schedule("QA", "Monday", "08:30", 45), Err(StudyTimerError::ScheduleOverlap(_))));
+        assert!(matches!(config.add_schedule("QA", "Monday", "08:00", 180), Err(StudyTimerError::ScheduleOverlap(_))));
+        assert!(matches!(config.add_schedule("QA", "Monday,Tuesday", "09:15", 15), Err(StudyTimerError::ScheduleOverlap(_))));
+        assert_eq!(config.schedules.get("QA").unwrap().len(), 1);
+
+        config.add_schedule("QA", "Sunday", "23:30", 60).unwrap();
+        assert!(matches!(config.add_schedule("QA", "Monday", "00:00", 15), Err(StudyTimerError::ScheduleOverlap(_))));
+    }
+
+    #[test]
+    fn test_add_schedule_adjacent_sessions() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+
+        config.add_schedule("QA", "Monday", "10:00", 30).unwrap();
+        config.add_schedule("QA", "Monday", "08:00", 60).unwrap();
+        config.add_schedule("QA", "Tuesday", "09:00", 60).unwrap();
+        assert_eq!(config.schedules.get("QA").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_session_end_time() {
+        assert_eq!(StudySession::new("Monday", "09:00", 90).end_time, "10:30");
+        assert_eq!(StudySession::new("Monday", "23:30", 60).end_time, "00:30");
+    }
+
+    #[test]
+    fn test_end_time_backfilled_from_duration() {
+        let session_str = r#"{"day":"Monday","start_time":"09:00","duration":45}"#;
+        let session: StudySession = serde_json::from_str(session_str).unwrap();
+        assert_eq!(session.end_time, "");
+
+        let mut config = create_test_config();
+        config.schedules.insert("QA".to_string(), vec![session]);
+        config.schedules.get_mut("QA").unwrap().push(StudySession {
+            end_time: "23:00".to_string(),
+            ..StudySession::new("Tuesday", "09:00", 30)
+        });
+        config.save().unwrap();
+
+        let loaded = Config::load_from(&config.config_path).unwrap();
+        let sessions = loaded.schedules.get("QA").unwrap();
+        assert_eq!(sessions[0].end_time, "09:45");
+        assert_eq!(sessions[1].end_time, "09:30");
+    }
+
+    #[test]
+    fn test_add_one_time_session() {
+        let mut config = create_test_config();
+        // 2024-01-22 is a Monday
+        let date = NaiveDate::from_ymd_opt(2024, 1, 22).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+        config.add_one_time("QA", "Monday", date, "14:00", 90, today).unwrap();
+
+        let sessions = config.schedules.get("QA").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].date, None);
+        assert_eq!(sessions[1].date, Some(date));
+        assert_eq!(sessions[1].day, "Monday");
+        assert_eq!(sessions[1].end_time, "15:30");
+
+        assert!(sessions[1].occurs_on(date));
+        assert!(!sessions[1].occurs_on(date + chrono::Duration::days(7)));
+        assert!(sessions[0].occurs_on(date + chrono::Duration::days(7)));
+
+        // clashes with the weekly monday session
+        let result = config.add_one_time("QA", "Monday", date, "09:30", 30, today);
+        assert!(matches!(result, Err(StudyTimerError::ScheduleOverlap(_))));
+
+        let result = config.add_one_time("QA", "Tuesday", date, "18:00", 30, today);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDay(_))));
+
+        let result = config.add_one_time("QA", "Monday", date, "18:00", 30, date + chrono::Duration::days(1));
+        assert!(matches!(result, Err(StudyTimerError::InvalidDate(_))));
+
+        let result = config.add_one_time("BE", "Monday", date, "18:00", 30, today);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_one_time_sessions_overlap() {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 22).unwrap();
+
+        let first = StudySession::once(monday, "09:00", 60);
+        let same_day = StudySession::once(monday, "09:30", 60);
+        let next_week = StudySession::once(monday + chrono::Duration::days(7), "09:30", 60);
+        assert!(first.overlaps(&same_day));
+        assert!(!first.overlaps(&next_week));
+
+        // sunday night running into monday morning
+        let sunday_night = StudySession::once(monday - chrono::Duration::days(1), "23:30", 60);
+        let monday_early = StudySession::once(monday, "00:15", 30);
+        assert!(sunday_night.overlaps(&monday_early));
+        assert!(!sunday_night.overlaps(&StudySession::once(monday + chrono::Duration::days(7), "00:15", 30)));
+    }
+
+    #[test]
+    fn test_remove_expired_sessions() {
+        let mut config = create_test_config();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let now = today.and_hms_opt(9, 0, 0).unwrap();
+
+        config.schedules.insert("QA".to_string(), vec![
+            StudySession::new("Monday", "09:00", 60),
+            StudySession::once(today - chrono::Duration::days(1), "10:00", 60),
+            StudySession::once(today, "10:00", 60),
+        ]);
+        config.schedules.insert("BE".to_string(), vec![StudySession::once(today - chrono::Duration::days(3), "10:00", 60)]);
+
+        assert_eq!(config.remove_expired_sessions(now), 2);
+        assert_eq!(config.schedules.get("QA").unwrap().len(), 2);
+        assert!(!config.schedules.contains_key("BE"));
+        assert_eq!(config.remove_expired_sessions(now), 0);
+
+        // a session crossing midnight is kept until it has ended, so it can still be credited
+        config.schedules.insert("OS".to_string(), vec![StudySession::once(today - chrono::Duration::days(1), "23:30", 60)]);
+        assert_eq!(config.remove_expired_sessions(today.and_hms_opt(0, 15, 0).unwrap()), 0);
+        assert_eq!(config.remove_expired_sessions(today.and_hms_opt(0, 30, 0).unwrap()), 0);
+        assert_eq!(config.remove_expired_sessions(today.and_hms_opt(0, 31, 0).unwrap()), 1);
+        assert!(!config.schedules.contains_key("OS"));
+    }
+
+    #[test]
+    fn test_add_schedule_zero_duration() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 0);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDuration(_))));
+        assert!(!config.schedules.contains_key("QA"));
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 1);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_add_schedule_too_long_duration() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 6000);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDuration(_))));
+        assert!(!config.schedules.contains_key("QA"));
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 1441);
+        assert!(matches!(result, Err(StudyTimerError::InvalidDuration(_))));
+
+        let result = config.add_schedule("QA", "Monday", "09:00", 1440);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_remove_subject() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+        config.add_schedule("QA", "Friday", "09:00", 60).unwrap();
+
+        let removed = config.remove_subject("QA").unwrap();
+        assert_eq!(removed, 2);
+        assert!(!config.subjects.contains_key("QA"));
+        assert!(!config.schedules.contains_key("QA"));
+        assert!(config.subjects.contains_key("OS"));
+
+        let removed = config.remove_subject("OS").unwrap();
+        assert_eq!(removed, 0);
+
+        let result = config.remove_subject("QA");
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+        config.add_schedule("OS", "Tuesday", "09:00", 60).unwrap();
+        assert!(config.check().is_empty());
+
+        // the kind of damage a hand edit can do
+        config.subjects.get_mut("OS").unwrap().target_hours = 0.0;
+        config.subjects.get_mut("QA").unwrap().completed_hours = 12.0;
+        config.schedules.insert("DB".to_string(), vec![StudySession::new("Funday", "9:00", 0)]);
+        config.schedules.get_mut("OS").unwrap().push(StudySession::new("Monday", "09:30", 30));
+
+        let problems = config.check();
+        assert_eq!(problems, vec![
+            "subject 'OS' has a target of 0 hours, it must be more than 0",
+            "subject 'QA' has 12 completed hours, more than its target of 10",
+            "schedule for 'DB' has 1 session(s) but there is no such subject",
+            "'DB' session on Funday at 9:00 has an unknown day 'Funday'",
+            "'DB' session on Funday at 9:00 has a malformed start time, expected HH:MM",
+            "'DB' session on Funday at 9:00 lasts 0 minutes, it must be between 1 and 1440",
+            "'OS' session on Monday at 09:30-10:00 overlaps 'QA' session on Monday at 09:00-10:00",
+        ]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.add_schedule("QA", "Monday", "09:00", 60).unwrap();
+        config.add_schedule("OS", "Friday", "09:00", 60).unwrap();
+        config.add_schedule("OS", "Saturday", "09:00", 60).unwrap();
+        config.set("neglect-days", "3").unwrap();
+
+        assert_eq!(config.clear(), (2, 3));
+        assert!(config.subjects.is_empty());
+        assert!(config.schedules.is_empty());
+        assert_eq!(config.neglect_days, 3);
+
+        assert_eq!(config.clear(), (0, 0));
+    }
+
+    #[test]
+    fn test_set_completed_hours() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+        config.log_hours("dsa", 4.0).unwrap();
+
+        assert_eq!(config.set_completed_hours("dsa", 2.5).unwrap(), 4.0);
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 2.5);
+
+        // past the target is allowed, it's only warned about
+        assert_eq!(config.set_completed_hours("dsa", 12.0).unwrap(), 2.5);
+        assert_eq!(config.set_completed_hours("dsa", 0.0).unwrap(), 12.0);
+
+        for invalid in [-1.0, f32::NAN, f32::INFINITY] {
+            let result = config.set_completed_hours("dsa", invalid);
+            assert!(matches!(result, Err(StudyTimerError::InvalidHours(_))));
+        }
+        assert_eq!(config.subjects.get("dsa").unwrap().completed_hours, 0.0);
+
+        let result = config.set_completed_hours("os", 1.0);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_set_tags() {
+        let mut config = create_test_config();
+        config.add_subject("dsa", 10.0, 0.0, false).unwrap();
+
+        let tags = vec![" University".to_string(), "cs".to_string(), "university".to_string()];
+        config.set_tags("dsa", &tags).unwrap();
+
+        let subject = config.subjects.get("dsa").unwrap();
+        assert_eq!(subject.tags, vec!["university", "cs"]);
+        assert!(subject.has_tag("UNIVERSITY"));
+        assert!(!subject.has_tag("hobby"));
+
+        let result = config.set_tags("dsa", &[" ".to_string()]);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTag(_))));
+        assert_eq!(config.subjects.get("dsa").unwrap().tags.len(), 2);
+
+        let result = config.set_tags("os", &tags);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_subject_without_tags_deserializes() {
+        let subject: Subject = serde_json::from_str(r#"{"target_hours":10.0,"completed_hours":2.0}"#).unwrap();
+        assert!(subject.tags.is_empty());
+        assert!(subject.weekly_target.is_none());
+    }
+
+    #[test]
+    fn test_week_start_of() {
+        // 2024-01-17 is a Wednesday
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        assert_eq!(Config::week_start_of(wednesday, Weekday::Mon), monday);
+        assert_eq!(Config::week_start_of(monday, Weekday::Mon), monday);
+        assert_eq!(Config::week_start_of(wednesday, Weekday::Sun), NaiveDate::from_ymd_opt(2024, 1, 14).unwrap());
+        assert_eq!(Config::week_start_of(wednesday, Weekday::Thu), NaiveDate::from_ymd_opt(2024, 1, 11).unwrap());
+    }
+
+    #[test]
+    fn test_weekly_reset() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        config.set_weekly_target("QA", 5.0).unwrap();
+        assert!(matches!(config.set_weekly_target("QA", -1.0), Err(StudyTimerError::InvalidTarget(_))));
+
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        assert!(config.reset_weekly(wednesday));
+        config.subjects.get_mut("QA").unwrap().weekly_completed = 3.0;
+
+        // later the same week, nothing to reset
+        assert!(!config.reset_weekly(wednesday + chrono::Duration::days(4)));
+        let subject = config.subjects.get("QA").unwrap().clone();
+        assert_eq!(config.weekly_completed(&subject, wednesday), 3.0);
+
+        // the next week reads as empty even before the reset has run
+        let next_monday = NaiveDate::from_ymd_opt(2024, 1, 22).unwrap();
+        assert_eq!(config.weekly_completed(&subject, next_monday), 0.0);
+
+        // the reset date survives a save and reload
+        config.save().unwrap();
+        let mut loaded = Config::load_from(&config.config_path).unwrap();
+        assert_eq!(loaded.last_weekly_reset, Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()));
+        assert!(!loaded.reset_weekly(wednesday));
+
+        assert!(loaded.reset_weekly(next_monday));
+        let subject = loaded.subjects.get("QA").unwrap();
+        assert_eq!(subject.weekly_completed, 0.0);
+        assert_eq!(subject.weekly_target, Some(5.0));
+        assert_eq!(subject.completed_hours, 0.0);
+    }
+
+    #[test]
+    fn test_set_timezone() {
+        let mut config = create_test_config();
+        assert!(config.timezone.is_none());
+
+        config.set("timezone", "Europe/Berlin").unwrap();
+        assert_eq!(config.timezone, Some(chrono_tz::Europe::Berlin));
+        assert!(matches!(config.set("timezone", "Mars/Olympus_Mons"), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.timezone, Some(chrono_tz::Europe::Berlin));
+
+        config.set("timezone", "local").unwrap();
+        assert!(config.timezone.is_none());
+    }
+
+    #[test]
+    fn test_wall_clock_across_spring_forward() {
+        let mut config = create_test_config();
+        config.timezone = Some(chrono_tz::America::New_York);
+
+        // on 2024-03-10 new york skips from 01:59 straight to 03:00
+        let before = chrono::Utc.with_ymd_and_hms(2024, 3, 10, 6, 59, 0).unwrap();
+        let after = chrono::Utc.with_ymd_and_hms(2024, 3, 10, 7, 0, 0).unwrap();
+        assert_eq!(config.wall_clock(&before).format("%Y-%m-%d %H:%M").to_string(), "2024-03-10 01:59");
+        assert_eq!(config.wall_clock(&after).format("%Y-%m-%d %H:%M").to_string(), "2024-03-10 03:00");
+
+        // the zone, not the instant's own offset, decides the wall clock
+        let berlin = chrono_tz::Europe::Berlin.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap();
+        assert_eq!(config.wall_clock(&berlin).format("%H:%M").to_string(), "03:00");
+    }
+
+    #[test]
+    fn test_set_week_start() {
+        let mut config = create_test_config();
+        assert_eq!(config.week_start, Weekday::Mon);
+
+        config.set("week-start", "Sunday").unwrap();
+        assert_eq!(config.week_start, Weekday::Sun);
+        config.set("week-start", "sat").unwrap();
+        assert_eq!(config.week_start, Weekday::Sat);
+        assert!(matches!(config.set("week-start", "someday"), Err(StudyTimerError::InvalidSetting(_))));
+
+        // a sunday week has already begun on sunday the 21st, a monday one only on the 22nd
+        config.set("week-start", "Sunday").unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 21).unwrap();
+        config.last_weekly_reset = NaiveDate::from_ymd_opt(2024, 1, 14);
+        assert!(config.reset_weekly(sunday));
+        assert_eq!(config.last_weekly_reset, Some(sunday));
+
+        config.week_start = Weekday::Mon;
+        config.last_weekly_reset = NaiveDate::from_ymd_opt(2024, 1, 15);
+        assert!(!config.reset_weekly(sunday));
+
+        // an older config reads as monday
+        let config_str = r#"{"subjects":{},"schedules":{},"config_path":"config.json"}"#;
+        let config: Config = serde_json::from_str(config_str).unwrap();
+        assert_eq!(config.week_start, Weekday::Mon);
+    }
+
+    #[test]
+    fn test_log_hours() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        assert!(config.subjects.get("QA").unwrap().last_studied.is_none());
+
+        config.log_hours("QA", 1.5).unwrap();
+        config.log_hours("QA", 2.0).unwrap();
+        assert_eq!(config.subjects.get("QA").unwrap().completed_hours, 3.5);
+        assert_eq!(config.subjects.get("QA").unwrap().weekly_completed, 3.5);
+
+        let last_studied = config.subjects.get("QA").unwrap().last_studied.unwrap();
+        assert!((Local::now() - last_studied).num_seconds().abs() < 5);
+        assert!(config.study_days.contains(&last_studied.date_naive()));
+        assert_eq!(config.study_days.len(), 1);
+
+        let result = config.log_hours("QA", -1.0);
+        assert!(matches!(result, Err(StudyTimerError::InvalidHours(_))));
+        assert_eq!(config.subjects.get("QA").unwrap().completed_hours, 3.5);
+
+        let result = config.log_hours("embedded", 1.0);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_set_reminder_lead_minutes() {
+        let mut config = create_test_config();
+        assert_eq!(config.reminder_lead_minutes, 5);
+
+        config.set("reminder-lead-minutes", "15").unwrap();
+        assert_eq!(config.reminder_lead_minutes, 15);
+
+        config.set("reminder-lead-minutes", "0").unwrap();
+        assert_eq!(config.reminder_lead_minutes, 0);
+
+        assert!(matches!(config.set("reminder-lead-minutes", "-5"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("reminder-lead-minutes", "soon"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("reminder-lead-minutes", "2000"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("lead", "10"), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.reminder_lead_minutes, 0);
+    }
+
+    #[test]
+    fn test_last_studied_defaults_when_missing() {
+        let config_str = r#"{"subjects":{"QA":{"target_hours":10.0,"completed_hours":2.0}},"schedules":{},"config_path":"config.json"}"#;
+        let config: Config = serde_json::from_str(config_str).unwrap();
+
+        let subject = config.subjects.get("QA").unwrap();
+        assert_eq!(subject.completed_hours, 2.0);
+        assert!(subject.last_studied.is_none());
+        assert!(subject.deadline.is_none());
+    }
+
+    #[test]
+    fn test_set_deadline() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+        assert!(config.subjects.get("QA").unwrap().deadline.is_none());
+
+        let deadline = NaiveDate::from_ymd_opt(2024, 6, 1);
+        config.set_deadline("QA", deadline).unwrap();
+        assert_eq!(config.subjects.get("QA").unwrap().deadline, deadline);
+
+        let result = config.set_deadline("embedded", deadline);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_reminder_lead_minutes_defaults_when_missing() {
+        let config_str = r#"{"subjects":{},"schedules":{},"config_path":"config.json"}"#;
+        let config: Config = serde_json::from_str(config_str).unwrap();
+        assert_eq!(config.reminder_lead_minutes, 5);
+        assert_eq!(config.notification_icon, "clock");
+        assert_eq!(config.notification_timeout_ms, 10000);
+        assert_eq!(config.notification_urgency, Urgency::Normal);
+        assert_eq!(config.notification_backend, Backend::Auto);
+        assert!(config.progress_colors);
+        assert_eq!(config.neglect_days, 5);
+        assert_eq!(config.reminder_template, "Time to study {subject} for {duration} minutes");
+    }
+
+    #[test]
+    fn test_set_notification_options() {
+        let mut config = create_test_config();
+
+        config.set("notification-icon", "alarm-clock").unwrap();
+        config.set("notification-timeout-ms", "3000").unwrap();
+        config.set("notification-urgency", "critical").unwrap();
+        config.set("notification-backend", "terminal").unwrap();
+        assert_eq!(config.notification_icon, "alarm-clock");
+        assert_eq!(config.notification_backend, Backend::Terminal);
+        assert_eq!(config.notification_timeout_ms, 3000);
+        assert_eq!(config.notification_urgency, Urgency::Critical);
+
+        assert!(matches!(config.set("notification-icon", " "), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("notification-timeout-ms", "long"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("notification-urgency", "urgent"), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.notification_urgency, Urgency::Critical);
+
+        config.set("notification-backend", "webhook").unwrap();
+        config.set("webhook-url", "https://hooks.slack.com/services/T000/B000/XXXX").unwrap();
+        assert_eq!(config.webhook_url.as_deref(), Some("https://hooks.slack.com/services/T000/B000/XXXX"));
+        assert!(matches!(config.set("webhook-url", "hooks.slack.com"), Err(StudyTimerError::InvalidSetting(_))));
+        config.set("webhook-url", "off").unwrap();
+        assert!(config.webhook_url.is_none());
+    }
+
+    #[test]
+    fn test_notification_templates() {
+        let mut config = create_test_config();
+        let session = StudySession::new("Monday", "09:00", 45);
+
+        assert_eq!(Config::render_template(&config.reminder_template, "QA", &session, 5), "Time to study QA for 45 minutes");
+        assert_eq!(Config::render_template(&config.pre_reminder_template, "QA", &session, 5), "QA study session starts in 5 minutes");
+
+        config.set("reminder-template", "{time}: {subject} ({duration} min)").unwrap();
+        assert_eq!(Config::render_template(&config.reminder_template, "QA", &session, 5), "09:00: QA (45 min)");
+        assert_eq!(Config::render_template(&config.reminder_template, "{time}", &session, 5), "09:00: {time} (45 min)");
+
+        assert!(matches!(config.set("reminder-template", "study {topic}"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("pre-reminder-template", "{subject starts soon"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("pre-reminder-template", " "), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.reminder_template, "{time}: {subject} ({duration} min)");
+        assert_eq!(config.pre_reminder_template, default_pre_reminder_template());
+    }
+
+    #[test]
+    fn test_set_neglect_days() {
+        let mut config = create_test_config();
+
+        config.set("neglect-days", "0").unwrap();
+        assert_eq!(config.neglect_days, 0);
+
+        config.set("neglect-days", "7").unwrap();
+        assert_eq!(config.neglect_days, 7);
+
+        assert!(matches!(config.set("neglect-days", "-1"), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.neglect_days, 7);
+    }
+
+    #[test]
+    fn test_set_digest() {
+        let mut config = create_test_config();
+        assert!(config.digest_time.is_none());
+
+        config.set("digest-time", "07:30").unwrap();
+        assert_eq!(config.digest_time.as_deref(), Some("07:30"));
+
+        config.set("digest-time", "8AM").unwrap();
+        assert_eq!(config.digest_time.as_deref(), Some("08:00"));
+
+        assert!(matches!(config.set("digest-time", "25:00"), Err(StudyTimerError::InvalidTime(_))));
+        assert_eq!(config.digest_time.as_deref(), Some("08:00"));
+
+        config.set("digest-time", "off").unwrap();
+        assert!(config.digest_time.is_none());
+
+        config.set("digest-empty-days", "true").unwrap();
+        assert!(config.digest_empty_days);
+        assert!(matches!(config.set("digest-empty-days", "yes"), Err(StudyTimerError::InvalidSetting(_))));
+    }
+
+    #[test]
+    fn test_set_dnd() {
+        let mut config = create_test_config();
+        assert!(config.dnd_window().is_none());
+
+        config.set("dnd", "22:00-07:00").unwrap();
+        assert_eq!(config.dnd_start.as_deref(), Some("22:00"));
+        assert_eq!(config.dnd_end.as_deref(), Some("07:00"));
+
+        assert!(matches!(config.set("dnd", "22:00"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(matches!(config.set("dnd", "22:00-25:00"), Err(StudyTimerError::InvalidTime(_))));
+        assert!(matches!(config.set("dnd", "07:00-07:00"), Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(config.dnd_start.as_deref(), Some("22:00"));
+
+        config.set("dnd", "off").unwrap();
+        assert!(config.dnd_window().is_none());
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let date = |day: &str| NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+        let today = date("2024-01-17");
+        let mut config = create_test_config();
+        assert!(!config.is_paused(today));
+
+        config.pause(Some(date("2024-01-20")), today).unwrap();
+        assert!(config.is_paused(today));
+        assert!(config.is_paused(date("2024-01-19")));
+        assert!(!config.is_paused(date("2024-01-20")));
+
+        assert!(matches!(config.pause(Some(today), today), Err(StudyTimerError::InvalidDate(_))));
+        assert_eq!(config.paused_until, Some(date("2024-01-20")));
+
+        config.pause(None, today).unwrap();
+        assert!(config.is_paused(date("2030-01-01")));
+
+        config.resume();
+        assert!(!config.is_paused(today));
+        assert!(config.paused_until.is_none());
+    }
+
+    #[test]
+    fn test_in_dnd() {
+        let time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        let mut config = create_test_config();
+        assert!(!config.in_dnd(time("03:00")));
+
+        config.set("dnd", "22:00-07:00").unwrap();
+        assert!(config.in_dnd(time("22:00")));
+        assert!(config.in_dnd(time("23:59")));
+        assert!(config.in_dnd(time("00:00")));
+        assert!(config.in_dnd(time("06:59")));
+        assert!(!config.in_dnd(time("07:00")));
+        assert!(!config.in_dnd(time("12:00")));
+        assert!(!config.in_dnd(time("21:59")));
+
+        config.set("dnd", "12:00-13:30").unwrap();
+        assert!(config.in_dnd(time("12:45")));
+        assert!(!config.in_dnd(time("13:30")));
+        assert!(!config.in_dnd(time("11:59")));
+
+        assert!(config.overlaps_dnd(time("11:30"), 60));
+        assert!(!config.overlaps_dnd(time("11:00"), 60));
+        assert!(!config.overlaps_dnd(time("13:30"), 60));
+    }
+
+    #[test]
+    fn test_set_progress_colors() {
+        let mut config = create_test_config();
+        assert!(config.progress_colors);
+
+        config.set("progress-colors", "false").unwrap();
+        assert!(!config.progress_colors);
+
+        assert!(matches!(config.set("progress-colors", "nope"), Err(StudyTimerError::InvalidSetting(_))));
+        assert!(!config.progress_colors);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut config = create_test_config();
+
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.add_schedule("DB", "Monday", "09:00", 50).unwrap();
+
+        let save_result = config.save();
+        assert!(save_result.is_ok());
+        assert!(config.config_path.exists());
+
+        let mut file = File::open(&config.config_path).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+
+        assert!(content.contains("DB"));
+        assert!(content.contains("Monday"));
+        assert!(content.contains("09:00"));
+
+        let loaded_config = Config::load_from(&config.config_path).unwrap();
+
+        assert!(loaded_config.subjects.contains_key("DB"));
+        assert_eq!(loaded_config.subjects.get("DB").unwrap().target_hours, 10.0);
+        assert!(loaded_config.schedules.contains_key("DB"));
+
+        let loaded_schedules = loaded_config.schedules.get("DB").unwrap();
+        assert_eq!(loaded_schedules[0].day, "Monday");
+        assert_eq!(loaded_schedules[0].start_time, "09:00");
+    }
+
+    #[test]
+    fn test_save_and_load_toml() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let mut config = Config {
+            config_path: temp_dir.path().join("config.toml"),
+            ..Config::default()
+        };
+
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.add_subject("operating systems", 5.5, 0.0, false).unwrap();
+        config.log_hours("DB", 2.25).unwrap();
+        config.add_schedule("DB", "Monday,Friday", "09:00", 50).unwrap();
+        config.add_schedule("operating systems", "Sunday", "23:30", 90).unwrap();
+        config.set("notification-urgency", "critical").unwrap();
+        config.set("progress-colors", "false").unwrap();
+        config.save().unwrap();
+
+        let content = fs::read_to_string(&config.config_path).unwrap();
+        assert!(toml::from_str::<toml::Table>(&content).is_ok());
+        assert!(serde_json::from_str::<serde_json::Value>(&content).is_err());
+
+        let loaded_config = Config::load_from(&config.config_path).unwrap();
+        assert_eq!(serde_json::to_value(&loaded_config).unwrap(), serde_json::to_value(&config).unwrap());
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(ConfigFormat::from_path(Path::new("config.json")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new("config.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("config.TOML")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("config")), ConfigFormat::Json);
+    }
+
+    #[test]
+    fn test_save_is_atomic() {
+        let mut config = create_test_config();
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.save().unwrap();
+        let temp_path = Config::sibling_path(&config.config_path, ".tmp");
+        assert!(!temp_path.exists());
+
+        // a directory in the way of the temp file makes the write fail before the rename
+        fs::create_dir_all(&temp_path).unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        assert!(config.save().is_err());
+
+        let loaded_config = Config::load_from(&config.config_path).unwrap();
+        assert!(loaded_config.subjects.contains_key("DB"));
+        assert!(!loaded_config.subjects.contains_key("OS"));
+
+        fs::remove_dir(&temp_path).unwrap();
+        config.save().unwrap();
+        let loaded_config = Config::load_from(&config.config_path).unwrap();
+        assert!(loaded_config.subjects.contains_key("OS"));
+    }
+
+    #[test]
+    fn test_save_keeps_backup() {
+        let mut config = create_test_config();
+        let backup_path = Config::backup_path(&config.config_path);
+
+        config.save().unwrap();
+        assert!(!backup_path.exists());
+
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.save().unwrap();
+        let backup = Config::load_from(&backup_path).unwrap();
+        assert!(backup.subjects.is_empty());
+
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.save().unwrap();
+        let backup = Config::load_from(&backup_path).unwrap();
+        assert!(backup.subjects.contains_key("DB"));
+        assert!(!backup.subjects.contains_key("OS"));
+    }
+
+    #[tokio::test]
+    async fn test_async_save_and_load() {
+        let mut config = create_test_config();
+        let backup_path = Config::backup_path(&config.config_path);
+        assert!(matches!(Config::load_async(&config.config_path).await, Err(StudyTimerError::ConfigNotFound(_))));
+
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.add_schedule("DB", "Monday", "09:00", 90).unwrap();
+        config.save_async().await.unwrap();
+        assert!(!backup_path.exists());
+
+        let loaded = Config::load_async(&config.config_path).await.unwrap();
+        assert_eq!(loaded.subjects.get("DB").unwrap().target_hours, 10.0);
+        assert_eq!(loaded.schedules.get("DB").unwrap()[0].end_time, "10:30");
+        assert_eq!(loaded.config_path, config.config_path);
+
+        // the backup is still the one the user's last save made
+        config.save().unwrap();
+        config.log_hours("DB", 1.5).unwrap();
+        config.save_async().await.unwrap();
+        assert_eq!(Config::load_async(&config.config_path).await.unwrap().subjects.get("DB").unwrap().completed_hours, 1.5);
+        assert_eq!(Config::load_from(&backup_path).unwrap().subjects.get("DB").unwrap().completed_hours, 0.0);
+        assert!(!Config::sibling_path(&config.config_path, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_busy_config() {
+        let mut config = create_test_config();
+        config.save().unwrap();
+
+        let timeout = Duration::from_millis(200);
+
+        // readers share the lock, a writer has to wait for all of them
+        let reader = Config::lock(&config.config_path, false).unwrap();
+        assert!(Config::load_from(&config.config_path).is_ok());
+        assert!(matches!(Config::lock_within(&config.config_path, true, timeout), Err(StudyTimerError::ConfigBusy(_))));
+
+        drop(reader);
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.save().unwrap();
+        assert!(Config::load_from(&config.config_path).unwrap().subjects.contains_key("DB"));
+
+        let writer = Config::lock(&config.config_path, true).unwrap();
+        assert!(matches!(Config::lock_within(&config.config_path, false, timeout), Err(StudyTimerError::ConfigBusy(_))));
+        drop(writer);
+        assert!(Config::lock_within(&config.config_path, false, timeout).is_ok());
+    }
+
+    #[test]
+    fn test_restore_backup() {
+        let mut config = create_test_config();
+        assert!(matches!(Config::restore_backup(&config.config_path), Err(StudyTimerError::BackupNotFound(_))));
+
+        config.add_subject("DB", 10.0, 0.0, false).unwrap();
+        config.save().unwrap();
+        config.remove_subject("DB").unwrap();
+        config.save().unwrap();
+
+        Config::restore_backup(&config.config_path).unwrap();
+        let restored = Config::load_from(&config.config_path).unwrap();
+        assert!(restored.subjects.contains_key("DB"));
+
+        // restoring again swaps back
+        Config::restore_backup(&config.config_path).unwrap();
+        let restored = Config::load_from(&config.config_path).unwrap();
+        assert!(!restored.subjects.contains_key("DB"));
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut config = create_test_config();
+        config.add_subject("DSA", 10.0, 0.0, false).unwrap();
+        config.add_schedule("DSA", "Monday", "09:00", 60).unwrap();
+        config.save().unwrap();
+
+        config.remove_subject("DSA").unwrap();
+        config.add_subject("OS", 5.0, 0.0, false).unwrap();
+        config.reminder_lead_minutes = 15;
+        config.save().unwrap();
+
+        let changes = Config::undo(&config.config_path).unwrap().unwrap();
+        assert_eq!(changes, vec![
+            "subject 'DSA' re-added".to_string(),
+            "subject 'OS' removed".to_string(),
+            "'DSA' session on Monday at 09:00 for 60 minutes re-added".to_string(),
+            format!("reminder-lead-minutes back to {}", default_reminder_lead_minutes()),
+        ]);
+        assert!(Config::load_from(&config.config_path).unwrap().subjects.contains_key("DSA"));
+
+        // a second undo takes the undo back
+        let changes = Config::undo(&config.config_path).unwrap().unwrap();
+        assert!(changes.contains(&"subject 'OS' re-added".to_string()));
+    }
+
+    #[test]
+    fn test_load_nonexistent_config() {
+        let temp_dir = tempdir().expect("failed to create temporary directory");
+        let non_existent_path = temp_dir.path().join("404.json");
+
+        let config = Config { config_path: non_existent_path, ..Default::default() };
+
+        let result = Config::load_from(&config.config_path);
+        assert!(matches!(result, Err(StudyTimerError::ConfigNotFound(_))));
+    }
+
+    #[test]
+    fn test_load_corrupted_config() {
+        let config = create_test_config();
+        fs::create_dir_all(config.config_path.parent().unwrap()).unwrap();
+        fs::write(&config.config_path, "{ \"subjects\": {").unwrap();
+
+        let result = Config::load_from(&config.config_path);
+        assert!(matches!(result, Err(StudyTimerError::ConfigCorrupted { .. })));
+        assert!(result.unwrap_err().to_string().contains("corrupted"));
+    }
+
+    #[test]
+    fn test_config_path_precedence() {
+        let flag = Some(PathBuf::from("/tmp/flag.json"));
+        let env_path = Some(OsString::from("/tmp/env.json"));
+
+        assert_eq!(Config::choose_config_path(flag.clone(), env_path.clone()), PathBuf::from("/tmp/flag.json"));
+        assert_eq!(Config::choose_config_path(None, env_path), PathBuf::from("/tmp/env.json"));
+        assert_eq!(Config::choose_config_path(None, Some(OsString::new())), Config::get_config_path());
+        assert_eq!(Config::choose_config_path(None, None), Config::get_config_path());
+    }
+
+    #[test]
+    fn test_profile_path() {
+        let profiles_dir = Path::new("/tmp/profiles");
+
+        assert_eq!(Config::profile_path("uni", profiles_dir).unwrap(), PathBuf::from("/tmp/profiles/uni.json"));
+        assert_eq!(Config::profile_path("aws-cert_2", profiles_dir).unwrap(), PathBuf::from("/tmp/profiles/aws-cert_2.json"));
+        assert_eq!(Config::profile_path(DEFAULT_PROFILE, profiles_dir).unwrap(), Config::get_config_path());
+
+        assert!(matches!(Config::profile_path("", profiles_dir), Err(StudyTimerError::InvalidProfile(_))));
+        assert!(matches!(Config::profile_path("../escape", profiles_dir), Err(StudyTimerError::InvalidProfile(_))));
+        assert!(matches!(Config::profile_path("a/b", profiles_dir), Err(StudyTimerError::InvalidProfile(_))));
+    }
+
+    #[test]
+    fn test_list_profiles() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let profiles_dir = temp_dir.path().join("profiles");
+
+        assert_eq!(Config::list_profiles_in(&profiles_dir).unwrap(), vec!["default"]);
+
+        fs::create_dir_all(&profiles_dir).unwrap();
+        fs::write(profiles_dir.join("uni.json"), "{}").unwrap();
+        fs::write(profiles_dir.join("cert.json"), "{}").unwrap();
+        fs::write(profiles_dir.join("uni.json.bak"), "{}").unwrap();
+        fs::write(profiles_dir.join("default.json"), "{}").unwrap();
+
+        assert_eq!(Config::list_profiles_in(&profiles_dir).unwrap(), vec!["default", "cert", "uni"]);
+    }
+
+    #[test]
+    fn test_12_hour_time() {
+        let time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+
+        assert_eq!(Config::parse_time("14:00").unwrap(), time("14:00"));
+        assert_eq!(Config::parse_time("2:00 PM").unwrap(), time("14:00"));
+        assert_eq!(Config::parse_time("2:30pm").unwrap(), time("14:30"));
+        assert_eq!(Config::parse_time("2PM").unwrap(), time("14:00"));
+        assert_eq!(Config::parse_time("9 am").unwrap(), time("09:00"));
+        assert_eq!(Config::parse_time("12:15 AM").unwrap(), time("00:15"));
+        assert_eq!(Config::parse_time("12PM").unwrap(), time("12:00"));
+
+        for garbage in ["14:00 PM", "0:30 AM", "13PM", "PM", "2:00 XM", "two PM", "2:75 PM", "-2PM"] {
+            assert!(matches!(Config::parse_time(garbage), Err(StudyTimerError::InvalidTime(_))), "{} parsed", garbage);
+        }
+    }
+
+    #[test]
+    fn test_schedule_times_stored_canonical() {
+        let mut config = create_test_config();
+        config.add_subject("QA", 10.0, 0.0, false).unwrap();
+
+        config.add_schedule("QA", "Monday", "2:30 PM", 60).unwrap();
+        config.add_schedule("QA", "Tuesday", "9:05", 60).unwrap();
+
+        let sessions = config.schedules.get("QA").unwrap();
+        assert_eq!(sessions[0].start_time, "14:30");
+        assert_eq!(sessions[0].end_time, "15:30");
+        assert_eq!(sessions[1].start_time, "09:05");
+
+        // removable by either form
+        config.remove_schedule("QA", "Monday", "14:30").unwrap();
+        config.remove_schedule("QA", "Tuesday", "9:05 AM").unwrap();
+        assert!(!config.schedules.contains_key("QA"));
+
+        config.set("dnd", "10PM-7:30 AM").unwrap();
+        assert_eq!(config.dnd_start.as_deref(), Some("22:00"));
+        assert_eq!(config.dnd_end.as_deref(), Some("07:30"));
+    }
+
+    #[test]
+    fn test_invalid_time_format() {
+        let mut config = create_test_config();
+        config.add_subject("OS", 10.0, 0.0, false).unwrap();
+
+        let result = config.add_schedule("OS", "Wednesday", "1000", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+
+        let result = config.add_schedule("OS", "Wednesday", "10:00:00", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+
+        let result = config.add_schedule("OS", "Wednesday", "24:00", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+
+        let result = config.add_schedule("OS", "Wednesday", "12:60", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+
+        let result = config.add_schedule("OS", "Wednesday", "", 60);
+        assert!(matches!(result, Err(StudyTimerError::InvalidTime(_))));
+
+        assert!(!config.schedules.contains_key("OS"));
+
+        let result = config.add_schedule("OS", "Wednesday", "23:59", 60);
+        assert!(result.is_ok());
     }
 }
@@ -0,0 +1,316 @@
+use chrono::{DateTime, Local};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::DEFAULT_PROFILE;
+use crate::error::StudyTimerError;
+
+#[derive(Clone)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+// written by the running daemon so `status`, a separate process, can report on it
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonHealth {
+    pub failed_notifications: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl PidFile {
+    pub fn new() -> Self {
+        Self::for_profile(DEFAULT_PROFILE)
+    }
+
+    // each profile gets its own pid file so daemons for different profiles can run side by side
+    pub fn for_profile(profile: &str) -> Self {
+        let file_name = if profile == DEFAULT_PROFILE {
+            "study_timer.pid".to_string()
+        } else {
+            format!("study_timer-{}.pid", profile)
+        };
+
+        Self {
+            path: Self::get_pid_dir().join(file_name),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&self) -> Result<(), StudyTimerError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.path, std::process::id().to_string())?;
+        // a new daemon starts with a clean record
+        self.remove_health()?;
+
+        Ok(())
+    }
+
+    fn health_path(&self) -> PathBuf {
+        self.path.with_extension("health")
+    }
+
+    pub fn write_health(&self, health: &DaemonHealth) -> Result<(), StudyTimerError> {
+        fs::write(self.health_path(), serde_json::to_string(health)?)?;
+        Ok(())
+    }
+
+    // nothing written yet means nothing has failed
+    pub fn read_health(&self) -> DaemonHealth {
+        fs::read_to_string(self.health_path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn tick_path(&self) -> PathBuf {
+        self.path.with_extension("tick")
+    }
+
+    // unlike the health file this outlives the daemon, a restart uses it to tell how long it was away
+    pub fn write_last_tick(&self, at: &DateTime<Local>) -> Result<(), StudyTimerError> {
+        fs::write(self.tick_path(), at.to_rfc3339())?;
+        Ok(())
+    }
+
+    pub fn read_last_tick(&self) -> Option<DateTime<Local>> {
+        let contents = fs::read_to_string(self.tick_path()).ok()?;
+        DateTime::parse_from_rfc3339(contents.trim()).ok().map(|at| at.with_timezone(&Local))
+    }
+
+    fn remove_health(&self) -> Result<(), StudyTimerError> {
+        if self.health_path().exists() {
+            fs::remove_file(self.health_path())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read(&self) -> Result<Option<u32>, StudyTimerError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&self.path)?;
+        let pid = contents.trim().parse::<u32>()
+            .map_err(|_| StudyTimerError::Daemon(format!("pid file '{}' is malformed", self.path.display())))?;
+
+        Ok(Some(pid))
+    }
+
+    // the pid file is written once when the daemon starts, so its mtime doubles as the start time
+    pub fn started_at(&self) -> Option<DateTime<Local>> {
+        let modified = fs::metadata(&self.path).ok()?.modified().ok()?;
+        Some(DateTime::<Local>::from(modified))
+    }
+
+    pub fn remove(&self) -> Result<(), StudyTimerError> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+
+        self.remove_health()
+    }
+
+    fn get_pid_dir() -> PathBuf {
+        if let Some(project_directories) = ProjectDirs::from("com", "study_timer", "study_timer") {
+            project_directories.cache_dir().to_path_buf()
+        } else {
+            PathBuf::from(".")
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    match kill(Pid::from_raw(pid as i32), None) {
+        Ok(()) => true,
+        Err(Errno::EPERM) => true,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_process_alive(_pid: u32) -> bool {
+    true
+}
+
+// forks into the background, the parent exits inside and only the child returns.
+// stdout and stderr, which carry the daemon's logs, are appended to the output file
+#[cfg(unix)]
+pub fn detach(pid_file: &PidFile, output: &Path) -> Result<(), StudyTimerError> {
+    // checked before forking so the error still reaches the terminal
+    if let Some(pid) = pid_file.read()? && is_process_alive(pid) {
+        return Err(StudyTimerError::Daemon(format!("daemon is already running (pid {})", pid)));
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let stdout = fs::OpenOptions::new().create(true).append(true).open(output)?;
+    let stderr = stdout.try_clone()?;
+
+    daemonize::Daemonize::new()
+        // daemonize moves to / by default, which would break a relative --config
+        .working_directory(std::env::current_dir()?)
+        .stdout(stdout)
+        .stderr(stderr)
+        .start()
+        .map_err(|e| StudyTimerError::Daemon(format!("failed to start in the background: {}", e)))
+}
+
+// there is no fork on windows, a scheduled task or service wrapper has to keep it running instead
+#[cfg(not(unix))]
+pub fn detach(_pid_file: &PidFile, _output: &Path) -> Result<(), StudyTimerError> {
+    Err(StudyTimerError::Daemon("--daemonize is only supported on unix, run 'study_timer start' from a scheduled task instead".to_string()))
+}
+
+#[cfg(unix)]
+pub fn terminate(pid: u32) -> Result<(), StudyTimerError> {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    kill(Pid::from_raw(pid as i32), Signal::SIGTERM)
+        .map_err(|e| StudyTimerError::Daemon(format!("failed to stop pid {}: {}", pid, e)))?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn terminate(_pid: u32) -> Result<(), StudyTimerError> {
+    Err(StudyTimerError::Daemon("stopping a daemon in another process is only supported on Unix".to_string()))
+}
+
+#[cfg(unix)]
+pub async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = sigterm.recv() => {},
+                _ = tokio::signal::ctrl_c() => {},
+            }
+        },
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        },
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_read_remove() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile { path: temp_dir.path().join("test.pid") };
+
+        assert_eq!(pid_file.read().unwrap(), None);
+
+        pid_file.write().unwrap();
+        assert_eq!(pid_file.read().unwrap(), Some(std::process::id()));
+
+        pid_file.remove().unwrap();
+        assert!(!pid_file.path().exists());
+        assert!(pid_file.remove().is_ok());
+    }
+
+    #[test]
+    fn test_health() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile { path: temp_dir.path().join("test.pid") };
+        assert_eq!(pid_file.read_health(), DaemonHealth::default());
+
+        let health = DaemonHealth { failed_notifications: 4, consecutive_failures: 2, last_error: Some("no dbus".to_string()) };
+        pid_file.write_health(&health).unwrap();
+        assert_eq!(pid_file.read_health(), health);
+
+        // a restart and a stop both clear it
+        pid_file.write().unwrap();
+        assert_eq!(pid_file.read_health(), DaemonHealth::default());
+        pid_file.write_health(&health).unwrap();
+        pid_file.remove().unwrap();
+        assert!(!temp_dir.path().join("test.health").exists());
+    }
+
+    #[test]
+    fn test_last_tick() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile { path: temp_dir.path().join("test.pid") };
+        assert!(pid_file.read_last_tick().is_none());
+
+        let at = Local::now();
+        pid_file.write_last_tick(&at).unwrap();
+        assert_eq!(pid_file.read_last_tick(), Some(at));
+
+        // kept through a stop and the next start
+        pid_file.write().unwrap();
+        pid_file.remove().unwrap();
+        assert_eq!(pid_file.read_last_tick(), Some(at));
+    }
+
+    #[test]
+    fn test_started_at() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile { path: temp_dir.path().join("test.pid") };
+        assert!(pid_file.started_at().is_none());
+
+        pid_file.write().unwrap();
+        let started_at = pid_file.started_at().unwrap();
+        assert!((Local::now() - started_at).num_seconds().abs() < 5);
+    }
+
+    #[test]
+    fn test_malformed_pid_file() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile { path: temp_dir.path().join("test.pid") };
+
+        fs::write(pid_file.path(), "not a pid").unwrap();
+        assert!(matches!(pid_file.read(), Err(StudyTimerError::Daemon(_))));
+    }
+
+    #[test]
+    fn test_for_profile() {
+        let default = PidFile::for_profile(DEFAULT_PROFILE);
+        assert_eq!(default.path(), PidFile::new().path());
+        assert_eq!(default.path().file_name().unwrap(), "study_timer.pid");
+
+        let uni = PidFile::for_profile("uni");
+        assert_eq!(uni.path().file_name().unwrap(), "study_timer-uni.pid");
+        assert_eq!(uni.path().parent(), default.path().parent());
+    }
+
+    #[test]
+    fn test_is_process_alive() {
+        assert!(is_process_alive(std::process::id()));
+
+        #[cfg(unix)]
+        assert!(!is_process_alive(i32::MAX as u32));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::config::Config;
+use crate::daemon::{self, PidFile};
+use crate::notification::{Backend, Notifier, Notify};
+use colored::Colorize;
+use directories::ProjectDirs;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+struct Check {
+    name: &'static str,
+    passed: bool,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Self { name, passed: true, detail, hint: None }
+    }
+
+    fn fail(name: &'static str, detail: String, hint: String) -> Self {
+        Self { name, passed: false, detail, hint: Some(hint) }
+    }
+
+    fn print(&self) {
+        let status = if self.passed { "[ok]  ".green().bold() } else { "[fail]".red().bold() };
+        println!("{} {}: {}", status, self.name, self.detail);
+
+        if let Some(hint) = &self.hint {
+            println!("       hint: {}", hint);
+        }
+    }
+}
+
+// runs every check and prints a line per check, returns true when all of them passed
+pub fn run(config_path: &Path) -> bool {
+    println!("{}", "study timer doctor:".bold());
+    println!("{}", "-".repeat(50));
+
+    let mut checks = vec![check_project_dirs()];
+    checks.extend(check_config(config_path));
+    checks.push(check_notifications());
+    checks.push(check_pid_file(&PidFile::new()));
+
+    for check in &checks {
+        check.print();
+    }
+
+    checks.iter().all(|check| check.passed)
+}
+
+fn check_project_dirs() -> Check {
+    match ProjectDirs::from("com", "study_timer", "study_timer") {
+        Some(dirs) => Check::pass("config directory", dirs.config_dir().display().to_string()),
+        None => Check::fail(
+            "config directory",
+            "could not resolve a home directory, using the current directory instead".to_string(),
+            "make sure $HOME is set for the user running study_timer".to_string(),
+        ),
+    }
+}
+
+fn check_config(config_path: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    if !config_path.exists() {
+        checks.push(Check::fail(
+            "config file",
+            format!("{} does not exist", config_path.display()),
+            "any command creates it on first use, or run 'study_timer init'".to_string(),
+        ));
+
+        let parent = config_path.parent().filter(|parent| parent.exists());
+        let writable = parent
+            .map(|parent| fs::metadata(parent).map(|meta| !meta.permissions().readonly()).unwrap_or(false))
+            .unwrap_or(true);
+
+        if !writable {
+            checks.push(Check::fail(
+                "config writable",
+                format!("{} is read-only", config_path.parent().unwrap_or(config_path).display()),
+                "fix the directory permissions so study_timer can create its config".to_string(),
+            ));
+        }
+
+        return checks;
+    }
+
+    checks.push(Check::pass("config file", config_path.display().to_string()));
+
+    match OpenOptions::new().append(true).open(config_path) {
+        Ok(_) => checks.push(Check::pass("config writable", "yes".to_string())),
+        Err(e) => checks.push(Check::fail(
+            "config writable",
+            e.to_string(),
+            format!("fix the permissions on {}", config_path.display()),
+        )),
+    }
+
+    match Config::load_from(config_path) {
+        Ok(config) => checks.push(Check::pass(
+            "config readable",
+            format!("{} subjects, {} scheduled subjects", config.subjects.len(), config.schedules.len()),
+        )),
+        Err(e) => checks.push(Check::fail(
+            "config readable",
+            e.to_string(),
+            "fix the file by hand or re-create it with 'study_timer init'".to_string(),
+        )),
+    }
+
+    checks
+}
+
+fn check_notifications() -> Check {
+    let notifier = Notifier::new().backend(Backend::Desktop);
+
+    match notifier.notify("Study Timer", "study_timer doctor test notification", None) {
+        Ok(()) => Check::pass("desktop notifications", "test notification sent".to_string()),
+        Err(e) => Check::fail(
+            "desktop notifications",
+            e.to_string(),
+            "start a notification daemon or run 'study_timer set notification-backend terminal'".to_string(),
+        ),
+    }
+}
+
+fn check_pid_file(pid_file: &PidFile) -> Check {
+    match pid_file.read() {
+        Ok(None) => Check::pass("daemon", "not running".to_string()),
+        Ok(Some(pid)) if daemon::is_process_alive(pid) => Check::pass("daemon", format!("running (pid {})", pid)),
+        Ok(Some(pid)) => Check::fail(
+            "daemon",
+            format!("stale pid file for pid {} at {}", pid, pid_file.path().display()),
+            "run 'study_timer stop' to clean it up".to_string(),
+        ),
+        Err(e) => Check::fail(
+            "daemon",
+            e.to_string(),
+            format!("delete {}", pid_file.path().display()),
+        ),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check_missing_config() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let checks = check_config(&temp_dir.path().join("config.json"));
+
+        assert!(!checks[0].passed);
+        assert!(checks[0].hint.as_ref().unwrap().contains("init"));
+    }
+
+    #[test]
+    fn test_check_valid_config() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config = Config {
+            config_path: temp_dir.path().join("config.json"),
+            ..Default::default()
+        };
+        config.save().unwrap();
+
+        let checks = check_config(&config.config_path);
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(|check| check.passed));
+    }
+
+    #[test]
+    fn test_check_corrupted_config() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, "{ not json").unwrap();
+
+        let checks = check_config(&config_path);
+        assert!(!checks.last().unwrap().passed);
+    }
+
+    #[test]
+    fn test_check_pid_file() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile::from_path(temp_dir.path().join("test.pid"));
+        assert!(check_pid_file(&pid_file).passed);
+
+        pid_file.write().unwrap();
+        assert!(check_pid_file(&pid_file).passed);
+
+        #[cfg(unix)]
+        {
+            fs::write(pid_file.path(), (i32::MAX as u32).to_string()).unwrap();
+            let check = check_pid_file(&pid_file);
+            assert!(!check.passed);
+            assert!(check.hint.unwrap().contains("stop"));
+        }
+
+        fs::write(pid_file.path(), "garbage").unwrap();
+        assert!(!check_pid_file(&pid_file).passed);
+    }
+}
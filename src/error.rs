@@ -0,0 +1,79 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StudyTimerError {
+    #[error("subject '{0}' not found..")]
+    SubjectNotFound(String),
+
+    #[error("subject '{0}' already exists, use 'edit' to change its target or --force to overwrite it")]
+    DuplicateSubject(String),
+
+    #[error("{0}")]
+    InvalidSubject(String),
+
+    #[error("{0}")]
+    InvalidTag(String),
+
+    #[error("{0}")]
+    InvalidTarget(String),
+
+    #[error("{0}")]
+    InvalidHours(String),
+
+    #[error("{0}")]
+    InvalidDay(String),
+
+    #[error("{0}")]
+    InvalidTime(String),
+
+    #[error("{0}")]
+    InvalidDate(String),
+
+    #[error("{0}")]
+    InvalidDuration(String),
+
+    #[error("{0}")]
+    ScheduleOverlap(String),
+
+    #[error("{0}")]
+    ScheduleNotFound(String),
+
+    #[error("{0}")]
+    InvalidSetting(String),
+
+    #[error("{0}")]
+    InvalidProfile(String),
+
+    #[error("{0}")]
+    Session(String),
+
+    #[error("{0}")]
+    Daemon(String),
+
+    #[error("{0}")]
+    Notification(String),
+
+    // a missing config can safely be created, a corrupted one holds data that must not be overwritten
+    #[error("config file not found at {}", .0.display())]
+    ConfigNotFound(PathBuf),
+
+    #[error("config file {} is corrupted: {source}", .path.display())]
+    ConfigCorrupted { path: PathBuf, source: Box<dyn std::error::Error + Send + Sync> },
+
+    #[error("no backup found at {}", .0.display())]
+    BackupNotFound(PathBuf),
+
+    #[error("config file {} is busy, another study_timer process is using it, try again", .0.display())]
+    ConfigBusy(PathBuf),
+
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    TomlSerialization(#[from] toml::ser::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -0,0 +1,387 @@
+use crate::config::StudySession;
+use crate::error::StudyTimerError;
+use crate::scheduler::{Scheduler, SubjectReport};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Utc, Weekday};
+use clap::ValueEnum;
+use std::io::Write;
+use std::iter;
+
+// RFC 5545 wants lines of at most 75 octets, longer ones are folded
+const ICS_LINE_LIMIT: usize = 75;
+
+const MARKDOWN_BAR_WIDTH: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Ics,
+    Markdown,
+}
+
+pub fn write<W: Write>(format: ExportFormat, reports: &[SubjectReport], now: DateTime<Local>, writer: W) -> Result<(), StudyTimerError> {
+    match format {
+        ExportFormat::Csv => write_csv(reports, writer),
+        ExportFormat::Ics => write_ics(reports, now, writer),
+        ExportFormat::Markdown => write_markdown(reports, now, writer),
+    }
+}
+
+fn write_csv<W: Write>(reports: &[SubjectReport], mut writer: W) -> Result<(), StudyTimerError> {
+    writeln!(writer, "subject,target_hours,completed_hours,percentage,scheduled_weekly_minutes")?;
+
+    for report in reports {
+        let weekly_minutes: u32 = report.sessions
+            .iter()
+            .filter(|session| session.date.is_none())
+            .map(|session| session.duration)
+            .sum();
+        let percentage = report.percentage
+            .map(|percentage| format!("{:.1}", percentage))
+            .unwrap_or_default();
+
+        writeln!(writer, "{},{},{},{},{}",
+                 csv_field(report.name),
+                 report.target_hours,
+                 report.completed_hours,
+                 percentage,
+                 weekly_minutes)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+// every weekly session becomes a recurring event starting on its next occurrence,
+// one-off sessions become a single event on their date
+fn write_ics<W: Write>(reports: &[SubjectReport], now: DateTime<Local>, mut writer: W) -> Result<(), StudyTimerError> {
+    let today = now.date_naive();
+    let stamp = now.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//study_timer//study_timer//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for report in reports {
+        for session in &report.sessions {
+            let (weekday, start_time) = match (session.day.parse::<Weekday>(), NaiveTime::parse_from_str(&session.start_time, "%H:%M")) {
+                (Ok(weekday), Ok(start_time)) => (weekday, start_time),
+                _ => continue,
+            };
+
+            let (start, when) = match session.date {
+                Some(date) => (date.and_time(start_time), date.format("%Y%m%d").to_string()),
+                None => (next_occurrence(today, weekday).and_time(start_time), weekday.to_string()),
+            };
+            let end = start + Duration::minutes(session.duration as i64);
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}-{}-{}@study_timer", uid_part(report.name), when, start_time.format("%H%M")));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+            lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            if session.date.is_none() {
+                lines.push(format!("RRULE:FREQ=WEEKLY;BYDAY={}", byday(weekday)));
+            }
+            lines.push(format!("SUMMARY:{}", ics_text(report.name)));
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(writer, "{}\r\n", fold_ics_line(&line))?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+// meant for pasting into github or notion, so the bars are plain text rather than coloured
+fn write_markdown<W: Write>(reports: &[SubjectReport], now: DateTime<Local>, mut writer: W) -> Result<(), StudyTimerError> {
+    let total_target_hours: f32 = reports.iter().map(|report| report.target_hours).sum();
+    let total_completed_hours: f32 = reports.iter().map(|report| report.completed_hours).sum();
+
+    writeln!(writer, "# Study plan")?;
+    writeln!(writer)?;
+    writeln!(writer, "_exported {}_", now.format("%Y-%m-%d"))?;
+    writeln!(writer)?;
+    writeln!(writer, "**Overall:** {} of {} hours {}", total_completed_hours, total_target_hours,
+             markdown_progress(Scheduler::percentage(total_completed_hours, total_target_hours)))?;
+    writeln!(writer)?;
+
+    writeln!(writer, "## Subjects")?;
+    writeln!(writer)?;
+    writeln!(writer, "| Subject | Target hours | Completed hours | Progress |")?;
+    writeln!(writer, "| --- | ---: | ---: | --- |")?;
+    for report in reports {
+        writeln!(writer, "| {} | {} | {} | {} |",
+                 markdown_text(report.name),
+                 report.target_hours,
+                 report.completed_hours,
+                 markdown_progress(report.percentage))?;
+    }
+    writeln!(writer)?;
+
+    writeln!(writer, "## Schedule")?;
+
+    let sessions: Vec<(&str, &StudySession)> = reports
+        .iter()
+        .flat_map(|report| report.sessions.iter().map(move |session| (report.name, *session)))
+        .collect();
+
+    for weekday in iter::successors(Some(Weekday::Mon), |weekday| Some(weekday.succ())).take(7) {
+        let mut day: Vec<&(&str, &StudySession)> = sessions
+            .iter()
+            .filter(|(_, session)| session.date.is_none() && session.day.parse::<Weekday>() == Ok(weekday))
+            .collect();
+        if day.is_empty() {
+            continue;
+        }
+        day.sort_by(|(a, a_session), (b, b_session)| a_session.start_time.cmp(&b_session.start_time).then(a.cmp(b)));
+
+        writeln!(writer)?;
+        writeln!(writer, "### {}", day[0].1.day)?;
+        writeln!(writer)?;
+        for (name, session) in day {
+            writeln!(writer, "- {}-{} {} ({} min)", session.start_time, session.end_time, markdown_text(name), session.duration)?;
+        }
+    }
+
+    let mut once: Vec<&(&str, &StudySession)> = sessions.iter().filter(|(_, session)| session.date.is_some()).collect();
+    if !once.is_empty() {
+        once.sort_by(|(a, a_session), (b, b_session)| {
+            (a_session.date, &a_session.start_time, a).cmp(&(b_session.date, &b_session.start_time, b))
+        });
+
+        writeln!(writer)?;
+        writeln!(writer, "### One-off")?;
+        writeln!(writer)?;
+        for (name, session) in once {
+            let date = session.date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default();
+            writeln!(writer, "- {} {}-{} {} ({} min)", date, session.start_time, session.end_time, markdown_text(name), session.duration)?;
+        }
+    }
+
+    if sessions.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "nothing scheduled")?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn markdown_progress(percentage: Option<f32>) -> String {
+    let Some(percentage) = percentage else {
+        return Scheduler::format_percentage(None);
+    };
+
+    // past 100% still draws a full bar, the percentage shows the surplus
+    let filled = ((percentage / 100.0 * MARKDOWN_BAR_WIDTH as f32).round() as usize).min(MARKDOWN_BAR_WIDTH);
+    format!("`{}{}` {}", "█".repeat(filled), "░".repeat(MARKDOWN_BAR_WIDTH - filled), Scheduler::format_percentage(Some(percentage)))
+}
+
+// subject names are free text, keep them from breaking the table or turning into formatting
+fn markdown_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn next_occurrence(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(days_ahead as i64)
+}
+
+fn byday(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn uid_part(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// continuation lines start with a single space, splits never land inside a utf-8 character
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+
+    for c in line.chars() {
+        if line_len + c.len_utf8() > ICS_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+
+    folded
+}
+
+// subject names are free text, so quote anything that would break the row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv() {
+        let monday = StudySession::new("Monday", "09:00", 60);
+        let friday = StudySession::new("Friday", "10:00", 45);
+        let reports = vec![
+            SubjectReport { name: "dsa", target_hours: 20.0, completed_hours: 5.0, percentage: Some(25.0), sessions: vec![&monday, &friday] },
+            SubjectReport { name: "os, \"kernels\"", target_hours: 10.0, completed_hours: 0.0, percentage: Some(0.0), sessions: vec![] },
+        ];
+
+        let mut output = Vec::new();
+        write(ExportFormat::Csv, &reports, Local::now(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "subject,target_hours,completed_hours,percentage,scheduled_weekly_minutes");
+        assert_eq!(lines[1], "dsa,20,5,25.0,105");
+        assert_eq!(lines[2], "\"os, \"\"kernels\"\"\",10,0,0.0,0");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_write_ics() {
+        let monday = StudySession::new("Monday", "09:00", 60);
+        let sunday = StudySession::new("Sunday", "23:30", 90);
+        let review = StudySession::once(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), "14:00", 120);
+        let reports = vec![
+            SubjectReport { name: "dsa; graphs", target_hours: 20.0, completed_hours: 5.0, percentage: Some(25.0), sessions: vec![&monday, &sunday, &review] },
+        ];
+
+        // a Wednesday
+        let now = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()
+            .and_hms_opt(12, 0, 0).unwrap()
+            .and_local_timezone(Local).unwrap();
+
+        let mut output = Vec::new();
+        write(ExportFormat::Ics, &reports, now, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(output.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(output.matches("BEGIN:VEVENT").count(), 3);
+        assert_eq!(output.matches("RRULE").count(), 2);
+
+        assert!(output.contains("DTSTART:20240122T090000\r\nDTEND:20240122T100000\r\nRRULE:FREQ=WEEKLY;BYDAY=MO\r\n"));
+        assert!(output.contains("DTSTART:20240121T233000\r\nDTEND:20240122T010000\r\nRRULE:FREQ=WEEKLY;BYDAY=SU\r\n"));
+        assert!(output.contains("SUMMARY:dsa\\; graphs\r\n"));
+        assert!(output.contains("UID:dsa--graphs-Mon-0900@study_timer\r\n"));
+        assert!(output.contains("UID:dsa--graphs-20240201-1400@study_timer\r\nDTSTAMP"));
+        assert!(output.contains("DTSTART:20240201T140000\r\nDTEND:20240201T160000\r\nSUMMARY"));
+    }
+
+    #[test]
+    fn test_write_markdown() {
+        let monday = StudySession::new("Monday", "09:00", 60);
+        let early = StudySession::new("Monday", "07:30", 30);
+        let friday = StudySession::new("Friday", "10:00", 45);
+        let review = StudySession::once(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), "14:00", 120);
+        let reports = vec![
+            SubjectReport { name: "dsa", target_hours: 20.0, completed_hours: 5.0, percentage: Some(25.0), sessions: vec![&monday, &friday, &review] },
+            SubjectReport { name: "os | kernels", target_hours: 10.0, completed_hours: 10.0, percentage: Some(100.0), sessions: vec![&early] },
+            SubjectReport { name: "reading", target_hours: 0.0, completed_hours: 0.0, percentage: None, sessions: vec![] },
+        ];
+
+        let now = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()
+            .and_hms_opt(12, 0, 0).unwrap()
+            .and_local_timezone(Local).unwrap();
+
+        let mut output = Vec::new();
+        write(ExportFormat::Markdown, &reports, now, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("# Study plan\n\n_exported 2024-01-17_\n\n"));
+        assert!(output.contains("**Overall:** 15 of 30 hours `██████████░░░░░░░░░░` 50.0%\n"));
+        assert!(output.contains("| dsa | 20 | 5 | `█████░░░░░░░░░░░░░░░` 25.0% |\n"));
+        assert!(output.contains("| os \\| kernels | 10 | 10 | `████████████████████` 100.0% |\n"));
+        assert!(output.contains("| reading | 0 | 0 | no data |\n"));
+        assert!(output.contains("### Monday\n\n- 07:30-08:00 os \\| kernels (30 min)\n- 09:00-10:00 dsa (60 min)\n"));
+        assert!(output.contains("### Friday\n\n- 10:00-10:45 dsa (45 min)\n"));
+        assert!(output.ends_with("### One-off\n\n- 2024-02-01 14:00-16:00 dsa (120 min)\n"));
+        assert!(output.find("### Monday").unwrap() < output.find("### Friday").unwrap());
+        assert!(!output.contains("### Thursday"));
+
+        let mut output = Vec::new();
+        write(ExportFormat::Markdown, &[], now, &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with("## Schedule\n\nnothing scheduled\n"));
+    }
+
+    #[test]
+    fn test_markdown_text() {
+        assert_eq!(markdown_text("dsa"), "dsa");
+        assert_eq!(markdown_text("c_sharp | *basics*"), "c\\_sharp \\| \\*basics\\*");
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        assert_eq!(next_occurrence(wednesday, Weekday::Wed), wednesday);
+        assert_eq!(next_occurrence(wednesday, Weekday::Thu), NaiveDate::from_ymd_opt(2024, 1, 18).unwrap());
+        assert_eq!(next_occurrence(wednesday, Weekday::Mon), NaiveDate::from_ymd_opt(2024, 1, 22).unwrap());
+    }
+
+    #[test]
+    fn test_fold_ics_line() {
+        let line = format!("SUMMARY:{}", "a".repeat(100));
+        let folded = fold_ics_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+
+        assert_eq!(fold_ics_line("VERSION:2.0"), "VERSION:2.0");
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("dsa"), "dsa");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,281 @@
+use crate::error::StudyTimerError;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub subject: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub minutes: f32,
+}
+
+impl HistoryEntry {
+    pub fn new(subject: &str, start: DateTime<Local>, end: DateTime<Local>, minutes: f32) -> Self {
+        Self {
+            subject: subject.to_string(),
+            start,
+            end,
+            minutes,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub total_minutes: f32,
+    // most studied first
+    pub per_subject: Vec<(String, f32)>,
+    pub sessions: usize,
+    pub average_minutes: f32,
+    pub busiest_day: (NaiveDate, f32),
+}
+
+impl Stats {
+    // None when nothing was studied since the given date, there is nothing to average
+    pub fn from_entries(entries: &[HistoryEntry], since: NaiveDate) -> Option<Self> {
+        let entries: Vec<&HistoryEntry> = entries
+            .iter()
+            .filter(|entry| entry.start.date_naive() >= since)
+            .collect();
+
+        if entries.is_empty() {
+            return None;
+        }
+
+        let mut per_subject: HashMap<&str, f32> = HashMap::new();
+        let mut per_day: BTreeMap<NaiveDate, f32> = BTreeMap::new();
+
+        for entry in &entries {
+            *per_subject.entry(&entry.subject).or_insert(0.0) += entry.minutes;
+            *per_day.entry(entry.start.date_naive()).or_insert(0.0) += entry.minutes;
+        }
+
+        let total_minutes: f32 = entries.iter().map(|entry| entry.minutes).sum();
+
+        let mut per_subject: Vec<(String, f32)> = per_subject
+            .into_iter()
+            .map(|(subject, minutes)| (subject.to_string(), minutes))
+            .collect();
+        per_subject.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        // ties go to the earlier day
+        let busiest_day = per_day
+            .into_iter()
+            .fold(None, |busiest: Option<(NaiveDate, f32)>, (day, minutes)| match busiest {
+                Some((_, most)) if most >= minutes => busiest,
+                _ => Some((day, minutes)),
+            })?;
+
+        Some(Self {
+            total_minutes,
+            per_subject,
+            sessions: entries.len(),
+            average_minutes: total_minutes / entries.len() as f32,
+            busiest_day,
+        })
+    }
+}
+
+// append-only json lines, one completed session per line
+#[derive(Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    // profiles share a directory, so only the plain config.* file gets the bare history.jsonl
+    pub fn for_config(config_path: &Path) -> Self {
+        let file_name = match config_path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if stem != "config" => format!("{}.{}", stem, HISTORY_FILE),
+            _ => HISTORY_FILE.to_string(),
+        };
+
+        Self {
+            path: config_path.with_file_name(file_name),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &HistoryEntry) -> Result<(), StudyTimerError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // a single write per line, an interrupted append can only ever damage the last line
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    // lines that don't parse, like a half-written last line, are skipped rather than failing the whole read
+    pub fn read(&self) -> Result<Vec<HistoryEntry>, StudyTimerError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    pub fn filter(entries: Vec<HistoryEntry>, subject: Option<&str>, since: Option<NaiveDate>) -> Vec<HistoryEntry> {
+        entries
+            .into_iter()
+            .filter(|entry| subject.is_none_or(|subject| entry.subject == subject))
+            .filter(|entry| since.is_none_or(|since| entry.start.date_naive() >= since))
+            .collect()
+    }
+
+    // hours per subject for sessions started within the range, both ends inclusive
+    pub fn hours_by_subject(entries: &[HistoryEntry], from: Option<NaiveDate>, to: Option<NaiveDate>) -> BTreeMap<String, f32> {
+        let mut hours = BTreeMap::new();
+
+        for entry in entries {
+            let day = entry.start.date_naive();
+            if from.is_some_and(|from| day < from) || to.is_some_and(|to| day > to) {
+                continue;
+            }
+            *hours.entry(entry.subject.clone()).or_insert(0.0) += entry.minutes / 60.0;
+        }
+
+        hours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(subject: &str, start: &str, minutes: f32) -> HistoryEntry {
+        let start = chrono::NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap();
+
+        HistoryEntry::new(subject, start, start + chrono::Duration::minutes(minutes as i64), minutes)
+    }
+
+    #[test]
+    fn test_for_config() {
+        let history = History::for_config(Path::new("/tmp/study_timer/config.json"));
+        assert_eq!(history.path(), Path::new("/tmp/study_timer/history.jsonl"));
+
+        let history = History::for_config(Path::new("/tmp/study_timer/profiles/uni.json"));
+        assert_eq!(history.path(), Path::new("/tmp/study_timer/profiles/uni.history.jsonl"));
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let history = History::for_config(&temp_dir.path().join("config.json"));
+
+        assert!(history.read().unwrap().is_empty());
+
+        let first = entry("dsa", "2024-01-15 09:00", 50.0);
+        let second = entry("os", "2024-01-16 10:00", 25.5);
+        history.append(&first).unwrap();
+        history.append(&second).unwrap();
+
+        assert_eq!(history.read().unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_read_skips_truncated_line() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let history = History::for_config(&temp_dir.path().join("config.json"));
+
+        let first = entry("dsa", "2024-01-15 09:00", 50.0);
+        history.append(&first).unwrap();
+
+        let mut file = OpenOptions::new().append(true).open(history.path()).unwrap();
+        file.write_all(b"{\"subject\":\"os\",\"sta").unwrap();
+
+        assert_eq!(history.read().unwrap(), vec![first]);
+    }
+
+    #[test]
+    fn test_stats() {
+        let entries = vec![
+            entry("dsa", "2024-01-08 09:00", 120.0),
+            entry("dsa", "2024-01-15 09:00", 50.0),
+            entry("os", "2024-01-15 14:00", 40.0),
+            entry("os", "2024-01-16 10:00", 25.0),
+            entry("dsa", "2024-01-17 09:00", 30.0),
+        ];
+
+        let stats = Stats::from_entries(&entries, NaiveDate::from_ymd_opt(2024, 1, 11).unwrap()).unwrap();
+        assert_eq!(stats.total_minutes, 145.0);
+        assert_eq!(stats.sessions, 4);
+        assert_eq!(stats.average_minutes, 36.25);
+        assert_eq!(stats.per_subject, vec![("dsa".to_string(), 80.0), ("os".to_string(), 65.0)]);
+        assert_eq!(stats.busiest_day, (NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), 90.0));
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let since = NaiveDate::from_ymd_opt(2024, 1, 11).unwrap();
+        assert!(Stats::from_entries(&[], since).is_none());
+
+        let old = vec![entry("dsa", "2024-01-08 09:00", 120.0)];
+        assert!(Stats::from_entries(&old, since).is_none());
+    }
+
+    #[test]
+    fn test_filter() {
+        let entries = vec![
+            entry("dsa", "2024-01-15 09:00", 50.0),
+            entry("os", "2024-01-16 10:00", 25.0),
+            entry("dsa", "2024-01-17 09:00", 30.0),
+        ];
+
+        assert_eq!(History::filter(entries.clone(), None, None).len(), 3);
+        assert_eq!(History::filter(entries.clone(), Some("dsa"), None).len(), 2);
+
+        let since = NaiveDate::from_ymd_opt(2024, 1, 16);
+        let filtered = History::filter(entries.clone(), None, since);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].subject, "os");
+
+        let filtered = History::filter(entries, Some("dsa"), since);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].minutes, 30.0);
+    }
+
+    #[test]
+    fn test_hours_by_subject() {
+        let entries = vec![
+            entry("dsa", "2023-12-31 09:00", 60.0),
+            entry("dsa", "2024-01-01 09:00", 90.0),
+            entry("os", "2024-01-15 10:00", 30.0),
+            entry("dsa", "2024-01-31 23:00", 30.0),
+            entry("os", "2024-02-01 10:00", 60.0),
+        ];
+
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1);
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31);
+        let hours = History::hours_by_subject(&entries, from, to);
+        assert_eq!(hours, BTreeMap::from([("dsa".to_string(), 2.0), ("os".to_string(), 0.5)]));
+
+        let hours = History::hours_by_subject(&entries, None, to);
+        assert_eq!(hours["dsa"], 3.0);
+        assert!(History::hours_by_subject(&entries, NaiveDate::from_ymd_opt(2024, 3, 1), None).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::StudyTimerError;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportedSubject {
+    pub name: String,
+    pub target_hours: f32,
+}
+
+// one per row, a bad row is reported and the rest are still imported
+#[derive(Debug, PartialEq)]
+pub struct ImportRow {
+    // "line 3" for csv, "entry 3" for json, both counted from 1
+    pub location: String,
+    pub subject: Result<ImportedSubject, String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+// a json array of {"name", "target_hours"} objects, anything else is read as csv
+pub fn parse(contents: &str) -> Result<Vec<ImportRow>, StudyTimerError> {
+    if contents.trim_start().starts_with('[') {
+        parse_json(contents)
+    } else {
+        Ok(parse_csv(contents))
+    }
+}
+
+fn parse_json(contents: &str) -> Result<Vec<ImportRow>, StudyTimerError> {
+    // only the array itself has to be valid, each entry is checked on its own
+    let entries: Vec<serde_json::Value> = serde_json::from_str(contents)?;
+
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| ImportRow {
+            location: format!("entry {}", index + 1),
+            subject: serde_json::from_value(entry).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+// name,target_hours per line. blank lines, # comments and a name/subject header are skipped
+fn parse_csv(contents: &str) -> Vec<ImportRow> {
+    let mut rows = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let subject = split_csv_line(line).and_then(|fields| match fields.as_slice() {
+            [name, _] if rows.is_empty() && (name.eq_ignore_ascii_case("name") || name.eq_ignore_ascii_case("subject")) => Ok(None),
+            [name, target_hours] => target_hours
+                .trim()
+                .parse::<f32>()
+                .map(|target_hours| Some(ImportedSubject { name: name.trim().to_string(), target_hours }))
+                .map_err(|_| format!("invalid target hours '{}'", target_hours.trim())),
+            _ => Err(format!("expected name,target_hours but found {} field(s)", fields.len())),
+        });
+
+        match subject {
+            Ok(None) => {},
+            Ok(Some(subject)) => rows.push(ImportRow { location: format!("line {}", index + 1), subject: Ok(subject) }),
+            Err(e) => rows.push(ImportRow { location: format!("line {}", index + 1), subject: Err(e) }),
+        }
+    }
+
+    rows
+}
+
+// quoted fields may hold commas, "" inside quotes is a literal quote, same as export writes them
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            },
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(name: &str, target_hours: f32) -> Result<ImportedSubject, String> {
+        Ok(ImportedSubject { name: name.to_string(), target_hours })
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let contents = "name,target_hours\n# semester 2\ndsa, 40\n\n\"networks, advanced\",12.5\nos,lots\ncompilers\n";
+        let rows = parse(contents).unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], ImportRow { location: "line 3".to_string(), subject: subject("dsa", 40.0) });
+        assert_eq!(rows[1], ImportRow { location: "line 5".to_string(), subject: subject("networks, advanced", 12.5) });
+        assert_eq!(rows[2].location, "line 6");
+        assert!(rows[2].subject.is_err());
+        assert_eq!(rows[3].location, "line 7");
+        assert!(rows[3].subject.is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let contents = r#"[{"name": "dsa", "target_hours": 40}, {"name": "os"}, {"name": "compilers", "target_hours": 8.5}]"#;
+        let rows = parse(contents).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].subject, subject("dsa", 40.0));
+        assert_eq!(rows[1].location, "entry 2");
+        assert!(rows[1].subject.is_err());
+        assert_eq!(rows[2].subject, subject("compilers", 8.5));
+
+        assert!(matches!(parse("[{\"name\": "), Err(StudyTimerError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(split_csv_line("a,b").unwrap(), vec!["a", "b"]);
+        assert_eq!(split_csv_line("\"say \"\"hi\"\"\",2").unwrap(), vec!["say \"hi\"", "2"]);
+        assert!(split_csv_line("\"open,2").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use directories::ProjectDirs;
+use std::io;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+const DEFAULT_FILTER: &str = "info";
+
+// RUST_LOG overrides the level, e.g. RUST_LOG=study_timer=debug. logs always go to stderr, with
+// log_to_file they are also written to a daily rotated file. the guard flushes that file when dropped
+pub fn init(profile: &str, log_to_file: bool) -> Option<WorkerGuard> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let (file_layer, guard) = if log_to_file {
+        let appender = tracing_appender::rolling::daily(log_dir(), log_file_name(profile));
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+        (Some(fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+    } else {
+        (None, None)
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(io::stderr))
+        .with(file_layer)
+        .init();
+
+    guard
+}
+
+pub fn log_dir() -> PathBuf {
+    if let Some(project_directories) = ProjectDirs::from("com", "study_timer", "study_timer") {
+        project_directories.cache_dir().join("logs")
+    } else {
+        PathBuf::from("./logs")
+    }
+}
+
+// stdout and stderr of a daemonized process
+pub fn output_path(profile: &str) -> PathBuf {
+    log_dir().join(format!("{}.out", file_stem(profile)))
+}
+
+fn log_file_name(profile: &str) -> String {
+    format!("{}.log", file_stem(profile))
+}
+
+// named like the pid file so profiles running side by side don't share a log
+fn file_stem(profile: &str) -> String {
+    if profile == crate::config::DEFAULT_PROFILE {
+        "study_timer".to_string()
+    } else {
+        format!("study_timer-{}", profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_name() {
+        assert_eq!(log_file_name("default"), "study_timer.log");
+        assert_eq!(log_file_name("uni"), "study_timer-uni.log");
+        assert_eq!(output_path("uni"), log_dir().join("study_timer-uni.out"));
+    }
+}
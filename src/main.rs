@@ -3,56 +3,433 @@ mod notification;
 mod schedule;
 mod scheduler;
 mod cli;
+mod daemon;
+mod doctor;
+mod error;
+mod export;
+mod history;
+mod import;
+mod logging;
+mod metrics;
+mod server;
+mod service;
+mod tui;
 
 use clap::Parser;
-use cli::{ Cli, Commands };
-use scheduler::Scheduler;
+use cli::{ Cli, Commands, Verbosity };
+use colored::Colorize;
+use config::{Config, DEFAULT_PROFILE};
+use daemon::PidFile;
+use error::StudyTimerError;
+use scheduler::{ListFilter, Scheduler, SubjectOptions};
+use std::io::{self, BufRead, Write};
 use std::process;
+use std::time::Instant;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+// informational output, dropped with --quiet. data the command was asked for, json and errors are printed directly
+macro_rules! say {
+    ($verbosity:expr, $($arg:tt)*) => {
+        if $verbosity != Verbosity::Quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
+// extra detail for --verbose, on stderr so it never mixes into piped output
+macro_rules! detail {
+    ($verbosity:expr, $($arg:tt)*) => {
+        if $verbosity == Verbosity::Verbose {
+            eprintln!("{}", format!($($arg)*).dimmed());
+        }
+    };
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
-    let mut scheduler = match Scheduler::new() {
+    // forking has to happen before the runtime starts its threads
+    if let Commands::Start { daemonize: true, .. } = cli.command {
+        Config::resolve_config_path(cli.config.clone(), cli.profile.as_deref())?;
+        let profile = cli.profile.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let output = logging::output_path(&profile);
+
+        say!(cli.verbosity(), "starting study timer daemon in the background, output goes to {}", output.display());
+        daemon::detach(&PidFile::for_profile(&profile), &output)?;
+    }
+
+    let verbosity = cli.verbosity();
+    let started = Instant::now();
+    let result = tokio::runtime::Runtime::new()?.block_on(run(cli));
+    detail!(verbosity, "finished in {:.1?}", started.elapsed());
+
+    result
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let verbosity = cli.verbosity();
+
+    // needs no config, so it works before study_timer is set up
+    if let Commands::Completions { shell } = cli.command {
+        Cli::write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Commands::Service { kind } = cli.command {
+        // the service won't start in this directory, so a relative --config has to be made absolute
+        let config = cli.config.as_deref().map(std::path::absolute).transpose()?;
+        print!("{}", service::unit(kind, &std::env::current_exe()?, cli.profile.as_deref(), config.as_deref()));
+        return Ok(());
+    }
+
+    // has to happen before anything is printed, piped output stays free of escape codes
+    if !cli.color_enabled() {
+        colored::control::set_override(false);
+    }
+    if cli.dry_run && !cli.command.supports_dry_run() {
+        eprintln!("--dry-run is not supported for this command");
+        process::exit(2);
+    }
+    let profile = cli.profile.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let config_path = Config::resolve_config_path(cli.config.clone(), cli.profile.as_deref())?;
+    detail!(verbosity, "profile {}, config {}", profile, config_path.display());
+
+    if cli.command == Commands::Profiles {
+        for name in Config::list_profiles()? {
+            if name == profile {
+                println!("* {}", name.green().bold());
+            } else {
+                println!("  {}", name);
+            }
+        }
+        return Ok(());
+    }
+
+    // doctor has to work without a usable config
+    if cli.command == Commands::Doctor {
+        if !doctor::run(&config_path) {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // restore has to work even when the current config is broken
+    if cli.command == Commands::Restore {
+        Config::restore_backup(&config_path)?;
+        say!(verbosity, "restored config from {}, run 'study_timer restore' again to undo", Config::backup_path(&config_path).display());
+        return Ok(());
+    }
+
+    // works off the backup alone, like restore, but reports what it changed
+    if cli.command == Commands::Undo {
+        match Config::undo(&config_path)? {
+            Some(changes) if changes.is_empty() => say!(verbosity, "restored the previous config, nothing in it differs from the current one"),
+            Some(changes) => {
+                for change in &changes {
+                    println!("{} {}", "restored:".green(), change);
+                }
+            },
+            None => say!(verbosity, "restored the previous config, the current one couldn't be read to compare"),
+        }
+        say!(verbosity, "only one change can be undone, run 'study_timer undo' again to redo it");
+        return Ok(());
+    }
+
+    // an explicit reset to defaults, the config it replaces is kept as the backup for undo
+    if let Commands::Init { yes } = cli.command {
+        if !yes && config_path.exists() && !confirm(&format!("replace the config at {} with the defaults? (y/N) ", config_path.display()))? {
+            say!(verbosity, "config left as it was");
+            return Ok(());
+        }
+        Scheduler::init(&config_path)?;
+        say!(verbosity, "Conf initialized at {}", config_path.display());
+        return Ok(());
+    }
+
+    let loading = Instant::now();
+    let first_run = !config_path.exists();
+    // a dry run leaves no trace, not even the config a first run would create
+    let scheduler = if first_run && cli.dry_run { Ok(Scheduler::unsaved(&config_path)) } else { Scheduler::new(&config_path) };
+    let scheduler = match scheduler {
         Ok(scheduler) => scheduler,
         Err(e) => {
             eprintln!("Failed to initialize scheduler: {}", e);
-            if cli.command == Commands::Init {
-                let scheduler = Scheduler::init()?;
-                println!("Conf initialized");
-                scheduler
-            } else {
-                eprintln!("run 'study_timer init' to create initial configuration");
-                process::exit(1);
+            if let StudyTimerError::ConfigCorrupted { .. } = e {
+                eprintln!("refusing to overwrite it, fix the file by hand or run 'study_timer restore' to roll back to {}",
+                          Config::backup_path(&config_path).display());
             }
+            process::exit(1);
         }
     };
+    // on stderr, like detail!, so it doesn't end up in --json or export output
+    if first_run && !cli.dry_run && verbosity != Verbosity::Quiet {
+        eprintln!("no config yet, created one at {}", config_path.display());
+    }
+    let mut scheduler = scheduler
+        .with_pid_file(PidFile::for_profile(&profile))
+        .with_ascii(cli.ascii)
+        .with_verbosity(verbosity)
+        .with_dry_run(cli.dry_run);
+    detail!(verbosity, "loaded {} subject(s) in {:.1?}", scheduler.subject_count(), loading.elapsed());
 
     match cli.command {
-        Commands::Init => {},
-        Commands::Add { subject, target_hours } => {
-            scheduler.add_subject(&subject, target_hours)?;
-            println!("Added subject '{}' with a target of {} hours", subject, target_hours);
+        Commands::Init { .. } | Commands::Doctor | Commands::Restore | Commands::Undo | Commands::Profiles | Commands::Completions { .. } | Commands::Service { .. } => {},
+        Commands::TestNotify => {
+            match scheduler.test_notification() {
+                Ok(()) => say!(verbosity, "Test notification sent"),
+                Err(e) => {
+                    eprintln!("failed to show the test notification: {}", e);
+                    process::exit(1);
+                }
+            }
         },
-        Commands::Schedule { subject, day, start_time, duration } => {
-            scheduler.add_schedule(&subject, &day, &start_time, duration)?;
-            println!("scheduled '{}' on {} at {} for {} minutes", subject, day, start_time, duration);
+        Commands::Add { subject, target_hours, completed, deadline, weekly_target, tags, force } => {
+            let subject = scheduler.add_subject(&subject, target_hours, completed, SubjectOptions { deadline, weekly_target, tags, force })?;
+            match deadline {
+                Some(deadline) => say!(verbosity, "Added subject '{}' with a target of {} hours by {}", subject, target_hours, deadline),
+                None => say!(verbosity, "Added subject '{}' with a target of {} hours", subject, target_hours),
+            }
+            if completed > 0.0 {
+                say!(verbosity, "Starting '{}' with {} hours already completed", subject, completed);
+            }
+            if let Some(weekly_target) = weekly_target {
+                say!(verbosity, "Weekly target for '{}' is {} hours", subject, weekly_target);
+            }
+        },
+        Commands::Edit { subject, target_hours, deadline, weekly_target, tags } => {
+            if let Some(target_hours) = target_hours {
+                let old_target = scheduler.edit_target(&subject, target_hours)?;
+                say!(verbosity, "Changed target for '{}' from {} to {} hours", subject, old_target, target_hours);
+            }
+            if let Some(deadline) = deadline {
+                scheduler.set_deadline(&subject, Some(deadline))?;
+                say!(verbosity, "Set deadline for '{}' to {}", subject, deadline);
+            }
+            if let Some(weekly_target) = weekly_target {
+                scheduler.set_weekly_target(&subject, weekly_target)?;
+                say!(verbosity, "Set weekly target for '{}' to {} hours", subject, weekly_target);
+            }
+            if !tags.is_empty() {
+                scheduler.set_tags(&subject, &tags)?;
+                say!(verbosity, "Set tags for '{}' to {}", subject, tags.join(", "));
+            }
+        },
+        Commands::Rename { old, new } => {
+            scheduler.rename_subject(&old, &new)?;
+            say!(verbosity, "Renamed subject '{}' to '{}'", old, new);
+        },
+        Commands::Clear { yes } => {
+            if !yes && !cli.dry_run && !confirm("remove all subjects and scheduled sessions? (y/N) ")? {
+                say!(verbosity, "nothing removed");
+                return Ok(());
+            }
+
+            let (removed_subjects, removed_sessions) = scheduler.clear()?;
+            say!(verbosity, "Removed {} subject(s) and {} scheduled session(s)", removed_subjects, removed_sessions);
+            if !cli.dry_run {
+                say!(verbosity, "run 'study_timer restore' to undo, before making any other change");
+            }
         },
-        Commands::List => {
-            scheduler.list_subjects();
+        Commands::Remove { subject } => {
+            let removed_sessions = scheduler.remove_subject(&subject)?;
+            say!(verbosity, "Removed subject '{}' and {} scheduled session(s)", subject, removed_sessions);
         },
-        Commands::Start => {
-            println!("starting study timer daemon...");
-            scheduler.run_daemon().await?;
+        Commands::Log { subject, hours } => {
+            scheduler.log_hours(&subject, hours)?;
+            say!(verbosity, "Logged {} hours for '{}'", hours, subject);
+        },
+        Commands::SetHours { subject, hours } => {
+            let old_hours = scheduler.set_completed_hours(&subject, hours)?;
+            say!(verbosity, "Changed completed hours for '{}' from {} to {}", subject, old_hours, hours);
+        },
+        Commands::Schedule { subject, day, start_time, duration, once: None, lead } => {
+            scheduler.add_schedule(&subject, &day, &start_time, duration, lead)?;
+            say!(verbosity, "scheduled '{}' on {} at {} for {} minutes", subject, day, start_time, duration);
+        },
+        Commands::Schedule { subject, day, start_time, duration, once: Some(date), lead } => {
+            scheduler.add_one_time(&subject, &day, date, &start_time, duration, lead)?;
+            say!(verbosity, "scheduled '{}' once on {} {} at {} for {} minutes", subject, day, date, start_time, duration);
+        },
+        Commands::Suggest { budget, session_length, apply } => {
+            let proposed = scheduler.suggest_sessions(budget, session_length)?;
+            if proposed.is_empty() {
+                say!(verbosity, "nothing to suggest, every subject is on track or already scheduled");
+            }
+
+            for (subject, session) in &proposed {
+                println!("  {} on {} at {}-{}", subject, session.day, session.start_time, session.end_time);
+                if apply {
+                    scheduler.add_schedule(subject, &session.day, &session.start_time, session.duration, None)?;
+                }
+            }
+
+            if apply && !proposed.is_empty() {
+                say!(verbosity, "scheduled {} suggested session(s)", proposed.len());
+            } else if !proposed.is_empty() {
+                say!(verbosity, "run again with --apply to add these sessions");
+            }
+        },
+        Commands::RemoveSchedule { subject, day, start_time } => {
+            let removed = scheduler.remove_schedule(&subject, &day, &start_time)?;
+            say!(verbosity, "removed '{}' session on {} at {}-{} ({} minutes)", subject, removed.day, removed.start_time, removed.end_time, removed.duration);
+        },
+        Commands::MoveSchedule { subject, day, start_time, new_day, new_start_time } => {
+            let (old, moved) = scheduler.move_schedule(&subject, &day, &start_time, &new_day, &new_start_time)?;
+            say!(verbosity, "moved '{}' session from {} at {} to {} at {}-{}", subject, old.day, old.start_time, moved.day, moved.start_time, moved.end_time);
+        },
+        Commands::CopySchedule { subject, from_day, to_day } => {
+            let (copied, skipped) = scheduler.copy_schedule(&subject, &from_day, &to_day)?;
+            for session in &copied {
+                say!(verbosity, "copied '{}' session to {} at {}-{} ({} minutes)", subject, session.day, session.start_time, session.end_time, session.duration);
+            }
+            for session in &skipped {
+                say!(verbosity, "skipped {} at {}-{}, it overlaps an existing '{}' session", session.day, session.start_time, session.end_time, subject);
+            }
+        },
+        Commands::Focus { subject, duration } => {
+            say!(verbosity, "focusing on '{}' for {} minutes, press Enter to pause or resume and Ctrl-C to stop early", subject, duration);
+            let hours = scheduler.focus(&subject, duration).await?;
+            say!(verbosity, "logged {:.2} hours for '{}'", hours, subject);
+        },
+        Commands::ResumeFocus => {
+            say!(verbosity, "resuming the interrupted focus session, press Enter to pause or resume and Ctrl-C to stop early");
+            let (subject, hours) = scheduler.resume_focus().await?;
+            say!(verbosity, "logged {:.2} hours for '{}'", hours, subject);
+        },
+        Commands::Pomodoro { subject, work, breaks, rounds } => {
+            say!(verbosity, "pomodoro on '{}': {} rounds of {}/{} minutes, press Enter to pause or resume and Ctrl-C to stop early", subject, rounds, work, breaks);
+            let hours = scheduler.pomodoro(&subject, work, breaks, rounds).await?;
+            say!(verbosity, "logged {:.2} hours for '{}'", hours, subject);
+        },
+        Commands::Set { key, value } => {
+            scheduler.set(&key, &value)?;
+            say!(verbosity, "set {} to {}", key, value);
+        },
+        Commands::List { json, tag, subjects, days } => {
+            let filter = ListFilter { subjects, days };
+            if json {
+                scheduler.print_subjects_json(tag.as_deref(), &filter)?;
+            } else {
+                scheduler.list_subjects(tag.as_deref(), &filter)?;
+            }
+        },
+        Commands::Next => {
+            scheduler.show_next();
+        },
+        Commands::Check => {
+            let problems = scheduler.check();
+            if problems.is_empty() {
+                say!(verbosity, "no problems found in {}", config_path.display());
+                return Ok(());
+            }
+
+            for problem in &problems {
+                println!("{} {}", "problem:".red().bold(), problem);
+            }
+            println!("{} problem(s) found in {}", problems.len(), config_path.display());
+            process::exit(1);
+        },
+        Commands::Summary => {
+            println!("{}", scheduler.summary_line(&chrono::Local::now().naive_local()));
+        },
+        Commands::Today => {
+            scheduler.show_today();
+        },
+        Commands::Serve { host, port } => {
+            let _log_guard = logging::init(&profile, false);
+            say!(verbosity, "serving the api for {} on http://{}:{}, press Ctrl-C to stop", config_path.display(), host, port);
+            server::serve(config_path.clone(), host, port).await?;
+        },
+        Commands::Tui => {
+            tui::run(&config_path, PidFile::for_profile(&profile), cli.ascii)?;
+        },
+        Commands::Conflicts => {
+            scheduler.show_conflicts();
+        },
+        Commands::Start { log_file, metrics_port, .. } => {
+            let _log_guard = logging::init(&profile, log_file);
+            if log_file {
+                say!(verbosity, "logging to {}", logging::log_dir().display());
+            }
+            if let Some(port) = metrics_port {
+                let listener = metrics::bind(port).await?;
+                say!(verbosity, "serving metrics on http://127.0.0.1:{}/metrics", port);
+                tokio::spawn(metrics::serve(listener, config_path.clone(), scheduler.metrics()));
+            }
+            say!(verbosity, "starting study timer daemon...");
+            let daemon = scheduler.run_daemon().await?;
+            daemon.await?;
         },
         Commands::Stop => {
-            println!("stopping study ttimer daemon...");
+            say!(verbosity, "stopping study ttimer daemon...");
             scheduler.stop_daemon()?;
         },
-        Commands::Progress => {
-            scheduler.show_progress();
+        Commands::Pause { until } => {
+            scheduler.pause(until)?;
+            match until {
+                Some(until) => say!(verbosity, "reminders paused until {}", until),
+                None => say!(verbosity, "reminders paused, run 'study_timer resume' to turn them back on"),
+            }
+        },
+        Commands::Resume => {
+            scheduler.resume()?;
+            say!(verbosity, "reminders resumed");
+        },
+        Commands::Status => {
+            scheduler.show_status()?;
+        },
+        Commands::Streak => {
+            scheduler.show_streak();
+        },
+        Commands::Stats => {
+            scheduler.show_stats()?;
+        },
+        Commands::History { subject, since, limit } => {
+            scheduler.show_history(subject.as_deref(), since, limit)?;
+        },
+        Commands::Progress { json, balanced, tag, from, to } => {
+            if from.is_some() || to.is_some() {
+                if json {
+                    scheduler.print_range_progress_json(from, to, tag.as_deref())?;
+                } else {
+                    scheduler.show_range_progress(from, to, tag.as_deref())?;
+                }
+            } else if json {
+                scheduler.print_progress_json(tag.as_deref())?;
+            } else {
+                scheduler.show_progress(balanced, tag.as_deref());
+            }
+        },
+        Commands::Import { path, merge } => {
+            let summary = scheduler.import(&path, merge)?;
+            for (location, e) in &summary.failed {
+                eprintln!("{} {}: {}", "failed".red(), location, e);
+            }
+            say!(verbosity, "imported from {}: {} added, {} updated, {} already existed, {} failed",
+                     path.display(), summary.added.len(), summary.updated.len(), summary.skipped.len(), summary.failed.len());
+        },
+        Commands::Export { format, path } => {
+            scheduler.export(format, path.as_deref())?;
+            if let Some(path) = path {
+                say!(verbosity, "exported {:?} to {}", format, path.display());
+            }
         },
     }
 
+    // printed even with --quiet, a dry run that looks like a real one would be misleading
+    if scheduler.has_unsaved_changes() {
+        println!("{} nothing was saved to {}", "(dry run)".yellow().bold(), config_path.display());
+    }
+
     Ok(())
 }
+
+// anything but y/yes, including a closed stdin, counts as no
+fn confirm(prompt: &str) -> io::Result<bool> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,132 @@
+use crate::config::Config;
+use crate::error::StudyTimerError;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+// counted by the daemon whether or not metrics are served, two atomics cost nothing
+#[derive(Debug, Default)]
+pub struct DaemonMetrics {
+    pub notifications_sent: AtomicU64,
+    pub notifications_failed: AtomicU64,
+}
+
+impl DaemonMetrics {
+    pub fn record(&self, sent: bool) {
+        let counter = if sent { &self.notifications_sent } else { &self.notifications_failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct MetricsState {
+    config_path: PathBuf,
+    metrics: Arc<DaemonMetrics>,
+}
+
+// bound up front so a taken port fails the start instead of a background task
+pub async fn bind(port: u16) -> Result<TcpListener, StudyTimerError> {
+    Ok(TcpListener::bind(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port)).await?)
+}
+
+pub async fn serve(listener: TcpListener, config_path: PathBuf, metrics: Arc<DaemonMetrics>) {
+    if let Ok(address) = listener.local_addr() {
+        info!(%address, "serving metrics");
+    }
+
+    let app = Router::new()
+        .route("/metrics", get(scrape))
+        .with_state(Arc::new(MetricsState { config_path, metrics }));
+
+    if let Err(e) = axum::serve(listener, app).await {
+        warn!(error = %e, "metrics server stopped");
+    }
+}
+
+// read fresh on each scrape, same as a daemon pass
+async fn scrape(State(state): State<Arc<MetricsState>>) -> Response {
+    match Config::load_async(&state.config_path).await {
+        Ok(config) => ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], render(&config, &state.metrics)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// prometheus text exposition format, subjects in name order so scrapes diff cleanly
+pub fn render(config: &Config, metrics: &DaemonMetrics) -> String {
+    let mut subjects: Vec<(&String, _)> = config.subjects.iter().collect();
+    subjects.sort_by_key(|(name, _)| *name);
+
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP study_timer_completed_hours Hours completed per subject.");
+    let _ = writeln!(out, "# TYPE study_timer_completed_hours gauge");
+    for (name, subject) in &subjects {
+        let _ = writeln!(out, "study_timer_completed_hours{{subject=\"{}\"}} {}", escape_label(name), subject.completed_hours);
+    }
+
+    let _ = writeln!(out, "# HELP study_timer_target_hours Target hours per subject.");
+    let _ = writeln!(out, "# TYPE study_timer_target_hours gauge");
+    for (name, subject) in &subjects {
+        let _ = writeln!(out, "study_timer_target_hours{{subject=\"{}\"}} {}", escape_label(name), subject.target_hours);
+    }
+
+    let _ = writeln!(out, "# HELP study_timer_notifications_total Notifications the daemon tried to show, by result.");
+    let _ = writeln!(out, "# TYPE study_timer_notifications_total counter");
+    let _ = writeln!(out, "study_timer_notifications_total{{result=\"sent\"}} {}", metrics.notifications_sent.load(Ordering::Relaxed));
+    let _ = writeln!(out, "study_timer_notifications_total{{result=\"failed\"}} {}", metrics.notifications_failed.load(Ordering::Relaxed));
+
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Subject;
+
+    fn subject(target_hours: f32, completed_hours: f32) -> Subject {
+        Subject {
+            target_hours,
+            completed_hours,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut config = Config::default();
+        config.subjects.insert("os".to_string(), subject(20.0, 0.0));
+        config.subjects.insert("dsa".to_string(), subject(40.0, 12.5));
+
+        let metrics = DaemonMetrics::default();
+        metrics.record(true);
+        metrics.record(true);
+        metrics.record(false);
+
+        let rendered = render(&config, &metrics);
+        assert!(rendered.contains("study_timer_completed_hours{subject=\"dsa\"} 12.5\nstudy_timer_completed_hours{subject=\"os\"} 0\n"));
+        assert!(rendered.contains("study_timer_target_hours{subject=\"dsa\"} 40\n"));
+        assert!(rendered.contains("study_timer_notifications_total{result=\"sent\"} 2\n"));
+        assert!(rendered.contains("study_timer_notifications_total{result=\"failed\"} 1\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("c \"advanced\" \\ 2\nnotes"), "c \\\"advanced\\\" \\\\ 2\\nnotes");
+    }
+}
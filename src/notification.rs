@@ -1,23 +1,359 @@
-use notify_rust::{Notification, NotificationHandle};
+use chrono::Local;
+use colored::Colorize;
+use notify_rust::{Notification, Timeout};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl FromStr for Urgency {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "low" => Ok(Urgency::Low),
+            "normal" => Ok(Urgency::Normal),
+            "critical" => Ok(Urgency::Critical),
+            _ => Err(format!("unknown urgency '{}', must be one of: low normal critical", value)),
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl From<Urgency> for notify_rust::Urgency {
+    fn from(urgency: Urgency) -> Self {
+        match urgency {
+            Urgency::Low => notify_rust::Urgency::Low,
+            Urgency::Normal => notify_rust::Urgency::Normal,
+            Urgency::Critical => notify_rust::Urgency::Critical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Desktop,
+    Terminal,
+    #[default]
+    Auto,
+    // a slack or discord incoming webhook, set with webhook-url
+    Webhook,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "desktop" => Ok(Backend::Desktop),
+            "terminal" => Ok(Backend::Terminal),
+            "auto" => Ok(Backend::Auto),
+            "webhook" => Ok(Backend::Webhook),
+            _ => Err(format!("unknown notification backend '{}', must be one of: desktop terminal auto webhook", value)),
+        }
+    }
+}
+
+pub trait Notify: Send + Sync {
+    // an urgency of None uses the notifier's configured one
+    fn notify(&self, title: &str, message: &str, urgency: Option<Urgency>) -> Result<(), Box<dyn Error>>;
+}
+
+// the webhook failed but the reminder was still shown locally. it counts as a failure,
+// only nothing needs to show it a second time
+#[derive(Debug)]
+pub struct ShownLocally(pub Box<dyn Error>);
+
+impl fmt::Display for ShownLocally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, shown locally instead", self.0)
+    }
+}
+
+impl Error for ShownLocally {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
 
 #[derive(Clone)]
 pub struct Notifier {
-} //TODO:I'll add configuration options later
+    icon: String,
+    timeout_ms: u32,
+    urgency: Urgency,
+    backend: Backend,
+    webhook_url: Option<String>,
+}
 
 impl Notifier {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            icon: "clock".to_string(),
+            timeout_ms: 10000,
+            urgency: Urgency::Normal,
+            backend: Backend::Auto,
+            webhook_url: None,
+        }
+    }
+
+    pub fn icon(mut self, icon: &str) -> Self {
+        self.icon = icon.to_string();
+        self
     }
 
-    pub fn notify(&self, title: &str, message: &str) -> Result<NotificationHandle, Box<dyn Error>> {
-        let notification = Notification::new()
+    // a timeout of 0 keeps the notification up until it is dismissed
+    pub fn timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn urgency(mut self, urgency: Urgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn webhook_url(mut self, webhook_url: Option<&str>) -> Self {
+        self.webhook_url = webhook_url.map(str::to_string);
+        self
+    }
+
+    fn notify_desktop(&self, title: &str, message: &str, urgency: Urgency) -> Result<(), Box<dyn Error>> {
+        let timeout = match self.timeout_ms {
+            0 => Timeout::Never,
+            timeout_ms => Timeout::Milliseconds(timeout_ms),
+        };
+
+        let mut notification = Notification::new();
+        notification
             .summary(title)
             .body(message)
-            .icon("clock")
-            .timeout(10000)
-            .show()?;
+            .icon(&self.icon)
+            .timeout(timeout);
+
+        // other platforms have no urgency hint, there it is simply ignored
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.urgency(urgency.into());
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        let _ = urgency;
+
+        notification.show()?;
+
+        Ok(())
+    }
+
+    // slack reads "text" and discord reads "content", each ignores the other's field
+    fn webhook_payload(title: &str, message: &str) -> Value {
+        json!({
+            "text": format!("*{}*: {}", title, message),
+            "content": format!("**{}**: {}", title, message),
+        })
+    }
+
+    fn notify_webhook(&self, title: &str, message: &str) -> Result<(), Box<dyn Error>> {
+        let url = self.webhook_url.clone().ok_or("the webhook backend needs a webhook-url setting")?;
+        let payload = Self::webhook_payload(title, message);
+
+        // reqwest's blocking client panics on an async runtime thread, which is where the daemon notifies from
+        let request = thread::spawn(move || -> Result<(), String> {
+            reqwest::blocking::Client::new()
+                .post(&url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .json(&payload)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        });
+        request.join().map_err(|_| "webhook request thread panicked")??;
+
+        Ok(())
+    }
+
+    // a network hiccup shouldn't cost a reminder, so the fallback shows it another way.
+    // the webhook's error is still returned, the daemon counts it as a failed notification
+    fn notify_webhook_or(&self, title: &str, message: &str, fallback: impl FnOnce()) -> Result<(), Box<dyn Error>> {
+        self.notify_webhook(title, message).map_err(|e| {
+            warn!(error = %e, "webhook notification failed, showing it locally instead");
+            fallback();
+            Box::new(ShownLocally(e)) as Box<dyn Error>
+        })
+    }
+
+    // headless machines and ssh sessions have no notification daemon, never drop the reminder
+    fn notify_local(&self, title: &str, message: &str, urgency: Urgency) {
+        if self.notify_desktop(title, message, urgency).is_err() {
+            self.notify_terminal(title, message);
+        }
+    }
+
+    fn notify_terminal(&self, title: &str, message: &str) {
+        println!("{}", Self::format_terminal(title, message));
+    }
+
+    fn format_terminal(title: &str, message: &str) -> String {
+        format!("[{}] {}: {}", Local::now().format("%Y-%m-%d %H:%M:%S"), title.bold(), message)
+    }
+}
+
+impl Notify for Notifier {
+    fn notify(&self, title: &str, message: &str, urgency: Option<Urgency>) -> Result<(), Box<dyn Error>> {
+        let urgency = urgency.unwrap_or(self.urgency);
+
+        match self.backend {
+            Backend::Desktop => self.notify_desktop(title, message, urgency),
+            Backend::Terminal => {
+                self.notify_terminal(title, message);
+                Ok(())
+            },
+            Backend::Auto => {
+                self.notify_local(title, message, urgency);
+                Ok(())
+            },
+            Backend::Webhook => self.notify_webhook_or(title, message, || self.notify_local(title, message, urgency)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_builder_options() {
+        let notifier = Notifier::new();
+        assert_eq!(notifier.icon, "clock");
+        assert_eq!(notifier.timeout_ms, 10000);
+        assert_eq!(notifier.urgency, Urgency::Normal);
+
+        let notifier = Notifier::new()
+            .icon("alarm")
+            .timeout_ms(3000)
+            .urgency(Urgency::Critical);
+        assert_eq!(notifier.icon, "alarm");
+        assert_eq!(notifier.timeout_ms, 3000);
+        assert_eq!(notifier.urgency, Urgency::Critical);
+    }
+
+    #[test]
+    fn test_terminal_backend_never_fails() {
+        let notifier = Notifier::new().backend(Backend::Terminal);
+        assert!(notifier.notify("Study Timer", "terminal notification", Some(Urgency::Critical)).is_ok());
+    }
+
+    // colored's override is process-wide and would race other tests, so escape codes are stripped instead
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+
+        plain
+    }
+
+    #[test]
+    fn test_format_terminal() {
+        let line = strip_ansi(&Notifier::format_terminal("Study Timer", "Time to study dsa"));
+        assert!(line.starts_with('['));
+        assert!(line.ends_with("] Study Timer: Time to study dsa"));
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("desktop".parse::<Backend>().unwrap(), Backend::Desktop);
+        assert_eq!("Terminal".parse::<Backend>().unwrap(), Backend::Terminal);
+        assert_eq!("auto".parse::<Backend>().unwrap(), Backend::Auto);
+        assert_eq!("Webhook".parse::<Backend>().unwrap(), Backend::Webhook);
+        assert!("dbus".parse::<Backend>().is_err());
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let payload = Notifier::webhook_payload("Study Timer", "Time to study dsa");
+        assert_eq!(payload["text"], "*Study Timer*: Time to study dsa");
+        assert_eq!(payload["content"], "**Study Timer**: Time to study dsa");
+    }
+
+    #[test]
+    fn test_webhook_posts_payload() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            // headers and the small json body arrive well within a few reads
+            while !String::from_utf8_lossy(&request).contains("Time to study dsa") {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let notifier = Notifier::new().backend(Backend::Webhook).webhook_url(Some(&url));
+        assert!(notifier.notify_webhook("Study Timer", "Time to study dsa").is_ok());
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.contains("\"content\":\"**Study Timer**: Time to study dsa\""));
+    }
+
+    #[test]
+    fn test_webhook_failure_falls_back() {
+        let fallbacks = Cell::new(0);
+        let notifier = Notifier::new().backend(Backend::Webhook);
+        let result = notifier.notify_webhook_or("Study Timer", "no url", || fallbacks.set(fallbacks.get() + 1));
+        assert!(result.is_err_and(|e| e.is::<ShownLocally>()));
+        assert_eq!(fallbacks.get(), 1);
+
+        // the port was free a moment ago and nothing listens on it now
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+
+        let notifier = notifier.webhook_url(Some(&url));
+        assert!(notifier.notify_webhook_or("Study Timer", "unreachable", || fallbacks.set(fallbacks.get() + 1)).is_err());
+        assert_eq!(fallbacks.get(), 2);
+    }
 
-        Ok(notification)
+    #[test]
+    fn test_urgency_from_str() {
+        assert_eq!("low".parse::<Urgency>().unwrap(), Urgency::Low);
+        assert_eq!("Critical".parse::<Urgency>().unwrap(), Urgency::Critical);
+        assert!("urgent".parse::<Urgency>().is_err());
     }
 }
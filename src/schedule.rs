@@ -1,5 +1,12 @@
-use chrono::{DateTime, Duration, Local, NaiveTime};
-use std::error::Error;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::StudyTimerError;
+
+const SESSION_FILE: &str = "session.json";
 
 pub struct Schedule {
     current_session: Option<StudySession>,
@@ -9,6 +16,19 @@ pub struct StudySession {
     pub subject: String,
     pub start_time: DateTime<Local>,
     pub end_time: DateTime<Local>,
+    pub paused: Duration,
+    pub studied: Duration,
+    paused_at: Option<DateTime<Local>>,
+}
+
+// what is written to disk while a session runs, enough to pick it up again after a crash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    pub subject: String,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub paused_seconds: i64,
+    pub saved_at: DateTime<Local>,
 }
 
 impl Schedule {
@@ -18,7 +38,7 @@ impl Schedule {
         }
     }
 
-    pub fn start_session(&mut self, subject: &str, duration_minutes: u32) -> Result<(), Box<dyn Error>> {
+    pub fn start_session(&mut self, subject: &str, duration_minutes: u32) -> Result<(), StudyTimerError> {
         let now = Local::now();
         let end_time = now + Duration::minutes(duration_minutes as i64);
 
@@ -26,13 +46,84 @@ impl Schedule {
             subject: subject.to_string(),
             start_time: now,
             end_time,
+            paused: Duration::zero(),
+            studied: Duration::zero(),
+            paused_at: None,
         });
 
         Ok(())
     }
 
+    // the returned session's `studied` excludes any time spent paused
     pub fn end_session(&mut self) -> Option<StudySession> {
-        self.current_session.take()
+        let mut session = self.current_session.take()?;
+        let now = Local::now();
+
+        if let Some(paused_at) = session.paused_at.take() {
+            session.paused += now - paused_at;
+        }
+
+        let end = now.min(session.end_time + session.paused);
+        session.studied = (end - session.start_time - session.paused).max(Duration::zero());
+
+        Some(session)
+    }
+
+    pub fn pause(&mut self) -> Result<(), StudyTimerError> {
+        let session = self.current_session.as_mut().ok_or_else(|| StudyTimerError::Session("no active session to pause".to_string()))?;
+
+        if session.paused_at.is_some() {
+            return Err(StudyTimerError::Session("session is already paused".to_string()));
+        }
+
+        session.paused_at = Some(Local::now());
+
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<(), StudyTimerError> {
+        let session = self.current_session.as_mut().ok_or_else(|| StudyTimerError::Session("no active session to resume".to_string()))?;
+
+        let paused_at = session.paused_at.take().ok_or_else(|| StudyTimerError::Session("session is not paused".to_string()))?;
+        session.paused += Local::now() - paused_at;
+
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.current_session
+            .as_ref()
+            .map(|session| session.paused_at.is_some())
+            .unwrap_or(false)
+    }
+
+    // a pause still in progress counts as paused up to now
+    pub fn snapshot(&self, now: DateTime<Local>) -> Option<SessionState> {
+        self.current_session.as_ref().map(|session| {
+            let pausing = session.paused_at.map(|paused_at| now - paused_at).unwrap_or_else(Duration::zero);
+
+            SessionState {
+                subject: session.subject.clone(),
+                start_time: session.start_time,
+                end_time: session.end_time,
+                paused_seconds: (session.paused + pausing).num_seconds(),
+                saved_at: now,
+            }
+        })
+    }
+
+    // nobody was studying between the last save and now, so that gap counts as paused
+    pub fn restore(&mut self, state: SessionState, now: DateTime<Local>) {
+        let downtime = (now - state.saved_at).max(Duration::zero());
+
+        self.current_session = Some(StudySession {
+            subject: state.subject,
+            start_time: state.start_time,
+            end_time: state.end_time,
+            paused: Duration::seconds(state.paused_seconds) + downtime,
+            studied: Duration::zero(),
+            paused_at: None,
+        });
     }
 
     pub fn get_current_session(&self) -> Option<&StudySession> {
@@ -41,9 +132,11 @@ impl Schedule {
 
     pub fn time_remaining(&self) -> Option<Duration> {
         self.current_session.as_ref().map(|session| {
-            let now = Local::now();
-            if now < session.end_time {
-                session.end_time - now
+            // while paused the clock stands still at the moment of pausing
+            let now = session.paused_at.unwrap_or_else(Local::now);
+            let end_time = session.end_time + session.paused;
+            if now < end_time {
+                end_time - now
             } else {
                 Duration::zero()
             }
@@ -51,10 +144,62 @@ impl Schedule {
     }
 }
 
+// lives next to the config like the history, profiles get their own
+#[derive(Clone)]
+pub struct SessionFile {
+    path: PathBuf,
+}
+
+impl SessionFile {
+    pub fn for_config(config_path: &Path) -> Self {
+        let file_name = match config_path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if stem != "config" => format!("{}.{}", stem, SESSION_FILE),
+            _ => SESSION_FILE.to_string(),
+        };
+
+        Self {
+            path: config_path.with_file_name(file_name),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // overwritten every tick, written to a sibling and renamed so a crash never leaves half a file
+    pub fn save(&self, state: &SessionState) -> Result<(), StudyTimerError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string(state)?)?;
+        fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+
+    pub fn load(&self) -> Result<Option<SessionState>, StudyTimerError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn remove(&self) -> Result<(), StudyTimerError> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
     use std::thread::sleep;
     use std::time::Duration as StdDuration;
 
@@ -77,7 +222,7 @@ mod tests {
         let time_difference = session.end_time.signed_duration_since(now);
 
         let minutes_difference = time_difference.num_minutes();
-        assert!(minutes_difference >= 59 && minutes_difference <= 60);
+        assert!((59..=60).contains(&minutes_difference));
     }
 
     #[test]
@@ -117,7 +262,9 @@ mod tests {
         let mut schedule = Schedule::new();
         assert!(schedule.time_remaining().is_none());
 
-        schedule.start_session("Rs", 3 * 60 / 60).unwrap();
+        // sessions are whole minutes, so cut this one down to three seconds
+        schedule.start_session("Rs", 1).unwrap();
+        schedule.current_session.as_mut().unwrap().end_time = Local::now() + Duration::seconds(3);
 
         let time = schedule.time_remaining().unwrap();
         let seconds = time.num_seconds();
@@ -153,6 +300,100 @@ mod tests {
         assert!(schedule.get_current_session().is_none());
     }
 
+    #[test]
+    fn test_pause_freezes_time_remaining() {
+        let mut schedule = Schedule::new();
+        assert!(schedule.pause().is_err());
+        assert!(!schedule.is_paused());
+
+        schedule.start_session("Rs", 1).unwrap();
+        schedule.pause().unwrap();
+        assert!(schedule.is_paused());
+        assert!(schedule.pause().is_err());
+
+        let before = schedule.time_remaining().unwrap();
+        sleep(StdDuration::from_millis(1100));
+        let after = schedule.time_remaining().unwrap();
+        assert_eq!(before, after);
+
+        schedule.resume().unwrap();
+        assert!(!schedule.is_paused());
+        assert!(schedule.resume().is_err());
+
+        let remaining = schedule.time_remaining().unwrap();
+        assert!(remaining.num_seconds() >= 57 && remaining.num_seconds() <= 59);
+
+        let session = schedule.get_current_session().unwrap();
+        assert!(session.paused.num_milliseconds() >= 1100);
+    }
+
+    #[test]
+    fn test_end_session_excludes_paused_time() {
+        let mut schedule = Schedule::new();
+        schedule.start_session("Rs", 60).unwrap();
+
+        sleep(StdDuration::from_millis(300));
+        schedule.pause().unwrap();
+        sleep(StdDuration::from_millis(1000));
+
+        let session = schedule.end_session().unwrap();
+        assert!(session.paused.num_milliseconds() >= 1000);
+        assert!(session.studied.num_milliseconds() >= 300);
+        assert!(session.studied.num_milliseconds() < 1000);
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut schedule = Schedule::new();
+        assert!(schedule.snapshot(Local::now()).is_none());
+
+        schedule.start_session("Rs", 60).unwrap();
+        let saved_at = Local::now() + Duration::minutes(20);
+        let state = schedule.snapshot(saved_at).unwrap();
+        assert_eq!(state.subject, "Rs");
+        assert_eq!(state.paused_seconds, 0);
+
+        // down for ten minutes after the save, they don't count as studied
+        let mut restored = Schedule::new();
+        restored.restore(state, saved_at + Duration::minutes(10));
+        let session = restored.get_current_session().unwrap();
+        assert_eq!(session.paused, Duration::minutes(10));
+
+        let remaining = restored.time_remaining().unwrap();
+        assert!(remaining.num_minutes() >= 69 && remaining.num_minutes() <= 70);
+    }
+
+    #[test]
+    fn test_snapshot_counts_ongoing_pause() {
+        let mut schedule = Schedule::new();
+        schedule.start_session("Rs", 60).unwrap();
+        schedule.pause().unwrap();
+
+        let state = schedule.snapshot(Local::now() + Duration::minutes(5)).unwrap();
+        assert!(state.paused_seconds >= 299 && state.paused_seconds <= 300);
+    }
+
+    #[test]
+    fn test_session_file() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let session_file = SessionFile::for_config(&temp_dir.path().join("config.json"));
+        assert_eq!(session_file.path(), temp_dir.path().join("session.json"));
+        assert_eq!(SessionFile::for_config(Path::new("/tmp/profiles/uni.json")).path(), Path::new("/tmp/profiles/uni.session.json"));
+
+        assert!(session_file.load().unwrap().is_none());
+        session_file.remove().unwrap();
+
+        let mut schedule = Schedule::new();
+        schedule.start_session("Rs", 30).unwrap();
+        let state = schedule.snapshot(Local::now()).unwrap();
+
+        session_file.save(&state).unwrap();
+        assert_eq!(session_file.load().unwrap(), Some(state));
+
+        session_file.remove().unwrap();
+        assert!(session_file.load().unwrap().is_none());
+    }
+
     #[test]
     fn test_session_time_boundaries() {
         let mut schedule = Schedule::new();
@@ -1,334 +1,3425 @@
+use crate::cli::Verbosity;
 use crate::config::{Config, Subject, StudySession};
-use crate::notification::Notifier;
-use chrono::{DateTime, Datelike, Local, NaiveTime, Timelike, Weekday};
-use std::collections::HashMap;
-use std::error::Error;
+use crate::notification::{Backend, Notifier, Notify, ShownLocally, Urgency};
+use crate::schedule::{Schedule, SessionFile};
+use crate::daemon::{self, DaemonHealth, PidFile};
+use crate::error::StudyTimerError;
+use crate::export::{self, ExportFormat};
+use crate::history::{History, HistoryEntry, Stats};
+use crate::import::{self, ImportSummary};
+use crate::metrics::DaemonMetrics;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::cell::Cell;
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::thread;
 use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::{task, time};
-use colored::Colorize;
+use colored::{Color, Colorize};
+use serde::Serialize;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+// reminders for subjects due within this many days are shown as critical
+const URGENT_DEADLINE_DAYS: i64 = 3;
+
+// after this many notifications in a row fail, reminders are also printed to the terminal
+const NOTIFY_FALLBACK_AFTER: u32 = 3;
+
+// the largest clock jump still treated as a dst shift rather than a suspend or a changed timezone
+const MAX_CLOCK_SHIFT_MINUTES: i64 = 120;
+
+// passes run every minute, a longer silence means the machine slept or the daemon was down
+const SLEEP_GAP_MINUTES: i64 = 3;
+
+// how far back a catch-up looks for sessions that went by unannounced
+const CATCH_UP_HOURS: i64 = 3;
+
+// a session is only announced once in this window, however many passes or reloads see it
+const NOTIFY_DEDUPE_MINUTES: i64 = 2;
+
+// (kind, subject, day, start time), the start popup and the pre-reminder are tracked separately
+type NotificationKey = (&'static str, String, String, String);
+
+// where the daemon reads the time from, tests swap in a fixed one
+type Clock = Arc<dyn Fn() -> DateTime<Local> + Send + Sync>;
+
+// a session together with the subject it belongs to
+type ScheduledSession<'a> = (&'a str, &'a StudySession);
+
+#[derive(Serialize)]
+pub struct SubjectReport<'a> {
+    pub name: &'a str,
+    pub target_hours: f32,
+    pub completed_hours: f32,
+    pub percentage: Option<f32>,
+    pub sessions: Vec<&'a StudySession>,
+}
+
+#[derive(Serialize)]
+pub struct ProgressReport<'a> {
+    pub subjects: Vec<SubjectReport<'a>>,
+    pub total_target_hours: f32,
+    pub total_completed_hours: f32,
+    pub overall_percentage: Option<f32>,
+    pub balanced_percentage: Option<f32>,
+}
+
+#[derive(Serialize)]
+pub struct NextReport<'a> {
+    pub subject: &'a str,
+    pub session: &'a StudySession,
+    pub minutes_until: i64,
+}
+
+pub enum DaemonState {
+    Running { pid: u32, since: Option<DateTime<Local>> },
+    // a pid file whose process is gone, left by a crash or kill -9
+    Stale(u32),
+    Stopped,
+}
+
+// the pieces of a progress bar, drawn as text by generate_progress_bar and as styled spans by the tui
+pub struct ProgressBar {
+    pub filled: usize,
+    pub empty: usize,
+    pub fill: &'static str,
+    pub blank: &'static str,
+    pub color: Color,
+}
+
+pub struct DashboardSubject {
+    pub name: String,
+    pub completed_hours: f32,
+    pub target_hours: f32,
+    pub percentage: Option<f32>,
+    pub bar: ProgressBar,
+}
+
+// what add can set besides the target and completed hours, the default sets none of it
+#[derive(Debug, Default)]
+pub struct SubjectOptions {
+    pub deadline: Option<NaiveDate>,
+    pub weekly_target: Option<f32>,
+    pub tags: Vec<String>,
+    pub force: bool,
+}
+
+// list --subject and --day, both repeatable, an empty one doesn't filter
+#[derive(Debug, Default)]
+pub struct ListFilter {
+    pub subjects: Vec<String>,
+    pub days: Vec<String>,
+}
+
+// a ListFilter checked against the config, names resolved and day shorthands expanded
+struct ListSelection<'a> {
+    subjects: Option<HashSet<&'a str>>,
+    days: Option<Vec<&'static str>>,
+}
+
+impl ListSelection<'_> {
+    fn shows_session(&self, session: &StudySession) -> bool {
+        self.days.as_ref().is_none_or(|days| days.iter().any(|day| *day == session.day))
+    }
+
+    // asked-for subjects always show, with only --day the ones with nothing on those days are left out
+    fn shows_subject(&self, name: &str, has_sessions: bool) -> bool {
+        match &self.subjects {
+            Some(subjects) => subjects.contains(name),
+            None => self.days.is_none() || has_sessions,
+        }
+    }
+}
+
+// everything the tui draws, gathered from a single config read
+pub struct Dashboard {
+    pub daemon: DaemonState,
+    pub paused: bool,
+    pub subjects: Vec<DashboardSubject>,
+    // (subject, session, ended) in start time order
+    pub today: Vec<(String, StudySession, bool)>,
+    // (subject, session, seconds until it starts)
+    pub next: Option<(String, StudySession, i64)>,
+}
+
+#[derive(Serialize)]
+struct RangeReport {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    subjects: BTreeMap<String, f32>,
+    total_hours: f32,
+}
+
+const STATS_DAYS: i64 = 7;
+// suggested sessions are placed on weekdays between these hours
+const SUGGEST_DAY_START_HOUR: u32 = 9;
+const SUGGEST_DAY_END_HOUR: u32 = 21;
+
+// the daemon's side of notifying, counts failures and falls back to the terminal when the backend keeps failing
+struct DaemonNotifier {
+    notifier: Arc<dyn Notify>,
+    fallback: Box<dyn Notify>,
+    pid_file: PidFile,
+    health: DaemonHealth,
+    metrics: Arc<DaemonMetrics>,
+}
+
+impl DaemonNotifier {
+    fn new(notifier: Arc<dyn Notify>, pid_file: PidFile, metrics: Arc<DaemonMetrics>) -> Self {
+        Self {
+            notifier,
+            fallback: Box::new(Notifier::new().backend(Backend::Terminal)),
+            pid_file,
+            health: DaemonHealth::default(),
+            metrics,
+        }
+    }
+
+    fn send(&mut self, title: &str, message: &str, urgency: Option<Urgency>) {
+        let result = self.notifier.notify(title, message, urgency);
+        self.metrics.record(result.is_ok());
+
+        match result {
+            Ok(()) => {
+                debug!(text = message, "notification sent");
+                if self.health.consecutive_failures == 0 {
+                    return;
+                }
+                self.health.consecutive_failures = 0;
+            },
+            Err(e) => {
+                error!(text = message, error = %e, "notification failed");
+                self.health.failed_notifications += 1;
+                self.health.consecutive_failures += 1;
+                self.health.last_error = Some(e.to_string());
+
+                // a failed webhook was already shown locally, printing it as well would show it twice
+                if self.health.consecutive_failures >= NOTIFY_FALLBACK_AFTER && !e.is::<ShownLocally>() {
+                    warn!(failures = self.health.consecutive_failures, "notifications keep failing, printing to the terminal instead");
+                    let _ = self.fallback.notify(title, message, urgency);
+                }
+            },
+        }
+
+        if let Err(e) = self.pid_file.write_health(&self.health) {
+            error!(path = %self.pid_file.path().display(), error = %e, "failed to record notification failures");
+        }
+    }
+}
 
 pub struct Scheduler {
     config: Config,
-    notifier: Notifier,
+    notifier: Arc<dyn Notify>,
     running: Arc<AtomicBool>,
+    stop: Arc<tokio::sync::Notify>,
+    pid_file: PidFile,
+    history: History,
+    // the running focus session, left behind when the process dies mid-session
+    session_file: SessionFile,
+    // plain # and - bars for terminals and logs that mangle the block characters
+    ascii: bool,
+    // quiet drops warnings and progress chatter, never the output a command was asked for
+    verbosity: Verbosity,
+    // changes are validated and applied in memory but never written
+    dry_run: bool,
+    unsaved_changes: Cell<bool>,
+    // the daemon's counters, read by the metrics endpoint
+    metrics: Arc<DaemonMetrics>,
+    clock: Clock,
 }
 
-impl Scheduler {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let config = Config::load()?;
-        let notifier = Notifier::new();
+impl Scheduler {
+    // a missing config is created the way init would, a corrupted one is an error so nothing overwrites it
+    pub fn new(config_path: &Path) -> Result<Self, StudyTimerError> {
+        let config = match Config::load_from(config_path) {
+            Ok(config) => config,
+            Err(StudyTimerError::ConfigNotFound(_)) => return Self::init(config_path),
+            Err(e) => return Err(e),
+        };
+        let notifier = Arc::new(Self::build_notifier(&config));
+
+        Ok(Self::with_notifier(config, notifier))
+    }
+
+    pub fn init(config_path: &Path) -> Result<Self, StudyTimerError> {
+        let scheduler = Self::unsaved(config_path);
+        scheduler.config.save()?;
+
+        Ok(scheduler)
+    }
+
+    // the defaults init would write, kept in memory only
+    pub fn unsaved(config_path: &Path) -> Self {
+        let config = Config {
+            config_path: config_path.to_path_buf(),
+            ..Config::default()
+        };
+        let notifier = Arc::new(Self::build_notifier(&config));
+
+        Self::with_notifier(config, notifier)
+    }
+
+    pub fn with_notifier(config: Config, notifier: Arc<dyn Notify>) -> Self {
+        let history = History::for_config(&config.config_path);
+        let session_file = SessionFile::for_config(&config.config_path);
+
+        Self {
+            config,
+            notifier,
+            running: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(tokio::sync::Notify::new()),
+            pid_file: PidFile::new(),
+            history,
+            session_file,
+            ascii: false,
+            verbosity: Verbosity::Normal,
+            dry_run: false,
+            unsaved_changes: Cell::new(false),
+            metrics: Arc::new(DaemonMetrics::default()),
+            clock: Arc::new(Local::now),
+        }
+    }
+
+    // the daemon always runs on the real clock, tests pin it to a fixed time
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: impl Fn() -> DateTime<Local> + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_pid_file(mut self, pid_file: PidFile) -> Self {
+        self.pid_file = pid_file;
+        self
+    }
+
+    pub fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    fn quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    // true when a dry run skipped a save, the command would have changed the config
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved_changes.get()
+    }
+
+    fn save(&self) -> Result<(), StudyTimerError> {
+        if self.dry_run {
+            self.unsaved_changes.set(true);
+            return Ok(());
+        }
+
+        self.config.save()
+    }
+
+    pub fn metrics(&self) -> Arc<DaemonMetrics> {
+        Arc::clone(&self.metrics)
+    }
+
+    pub fn subject_count(&self) -> usize {
+        self.config.subjects.len()
+    }
+
+    fn build_notifier(config: &Config) -> Notifier {
+        Notifier::new()
+            .icon(&config.notification_icon)
+            .timeout_ms(config.notification_timeout_ms)
+            .urgency(config.notification_urgency)
+            .backend(config.notification_backend)
+            .webhook_url(config.webhook_url.as_deref())
+    }
+
+    // everything is applied in memory first, so the add is a single save and a single undo step
+    pub fn add_subject(&mut self, name: &str, target_hours: f32, completed_hours: f32, options: SubjectOptions) -> Result<String, StudyTimerError> {
+        // checked up front so a bad weekly target or tag doesn't leave a half-added subject behind
+        if let Some(weekly_target) = options.weekly_target {
+            Config::validate_target(weekly_target)?;
+        }
+        Config::normalize_tags(&options.tags)?;
+
+        let name = self.config.add_subject(name, target_hours, completed_hours, options.force)?;
+        if options.deadline.is_some() {
+            self.config.set_deadline(&name, options.deadline)?;
+        }
+        if let Some(weekly_target) = options.weekly_target {
+            self.config.set_weekly_target(&name, weekly_target)?;
+        }
+        if !options.tags.is_empty() {
+            self.config.set_tags(&name, &options.tags)?;
+        }
+        self.save()?;
+        Ok(name)
+    }
+
+    // a lead overrides reminder_lead_minutes for the new sessions
+    pub fn add_schedule(&mut self, subject: &str, day: &str, start_time: &str, duration: u32, lead_minutes: Option<u32>) -> Result<(), StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+        let existing = self.config.schedules.get(subject).map(|sessions| sessions.len()).unwrap_or(0);
+
+        lead_minutes.map(Config::validate_lead).transpose()?;
+        self.config.add_schedule(subject, day, start_time, duration)?;
+        self.config.set_lead(subject, existing, lead_minutes);
+        self.save()?;
+
+        self.warn_conflicts(subject, existing);
+        self.warn_shortfall(subject);
+
+        Ok(())
+    }
+
+    pub fn add_one_time(&mut self, subject: &str, day: &str, date: NaiveDate, start_time: &str, duration: u32, lead_minutes: Option<u32>) -> Result<(), StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+        let existing = self.config.schedules.get(subject).map(|sessions| sessions.len()).unwrap_or(0);
+
+        lead_minutes.map(Config::validate_lead).transpose()?;
+        self.config.add_one_time(subject, day, date, start_time, duration, Local::now().date_naive())?;
+        self.config.set_lead(subject, existing, lead_minutes);
+        self.save()?;
+
+        self.warn_conflicts(subject, existing);
+        self.warn_shortfall(subject);
+
+        Ok(())
+    }
+
+    // sessions from index `existing` on are the ones just added
+    fn warn_conflicts(&self, subject: &str, existing: usize) {
+        if self.quiet() {
+            return;
+        }
+
+        if let Some(sessions) = self.config.schedules.get(subject) {
+            for session in &sessions[existing..] {
+                for (other_subject, other) in self.conflicts_with(subject, session) {
+                    println!("{} {} on {} at {} clashes with {} on {} at {} for {} minutes",
+                             "warning:".yellow().bold(),
+                             subject, session.describe_day(), session.start_time,
+                             other_subject, other.describe_day(), other.start_time, other.duration);
+                }
+            }
+        }
+    }
+
+    pub fn find_conflicts(&self) -> Vec<(ScheduledSession<'_>, ScheduledSession<'_>)> {
+        let mut all_sessions: Vec<ScheduledSession> = self.config.schedules
+            .iter()
+            .flat_map(|(subject, sessions)| sessions.iter().map(move |session| (subject.as_str(), session)))
+            .collect();
+
+        all_sessions.sort_by_key(|(subject, session)| (*subject, session.week_interval()));
+
+        let mut conflicts = Vec::new();
+        for (i, first) in all_sessions.iter().enumerate() {
+            for second in &all_sessions[i + 1..] {
+                if first.1.overlaps(second.1) {
+                    conflicts.push((*first, *second));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    pub fn show_conflicts(&self) {
+        let conflicts = self.find_conflicts();
+
+        if conflicts.is_empty() {
+            println!("no schedule conflicts found");
+            return;
+        }
+
+        println!("{}", "Schedule conflicts:".bold());
+        println!("{}", "-".repeat(50));
+
+        for ((subject, session), (other_subject, other)) in conflicts {
+            println!("{} {} at {} for {} minutes {} {} {} at {} for {} minutes",
+                     subject.red().bold(), session.describe_day(), session.start_time, session.duration,
+                     "clashes with".bold(),
+                     other_subject.red().bold(), other.describe_day(), other.start_time, other.duration);
+        }
+    }
+
+    fn conflicts_with(&self, subject: &str, session: &StudySession) -> Vec<(&str, &StudySession)> {
+        self.config.schedules
+            .iter()
+            .filter(|(other_subject, _)| other_subject.as_str() != subject)
+            .flat_map(|(other_subject, sessions)| sessions.iter().map(move |other| (other_subject.as_str(), other)))
+            .filter(|(_, other)| other.overlaps(session))
+            .collect()
+    }
+
+    pub fn edit_target(&mut self, name: &str, target_hours: f32) -> Result<f32, StudyTimerError> {
+        let old_target = self.config.edit_target(name, target_hours)?;
+        self.save()?;
+        Ok(old_target)
+    }
+
+    pub fn set_completed_hours(&mut self, name: &str, hours: f32) -> Result<f32, StudyTimerError> {
+        let name = &self.config.resolve_subject(name)?.to_string();
+        let old_hours = self.config.set_completed_hours(name, hours)?;
+        self.save()?;
+
+        if let Some(subject) = self.config.subjects.get(name).filter(|_| !self.quiet()) && hours > subject.target_hours {
+            println!("{} {} hours is more than the {} hour target for '{}'",
+                     "warning:".yellow().bold(), hours, subject.target_hours, name);
+        }
+
+        Ok(old_hours)
+    }
+
+    // greedy: the weekly budget is shared out by remaining hours, then each session goes in the first free
+    // weekday slot outside do-not-disturb, preferring days the subject isn't studied on yet
+    pub fn suggest_sessions(&self, budget_hours: f32, session_length: u32) -> Result<Vec<(String, StudySession)>, StudyTimerError> {
+        if budget_hours <= 0.0 || !budget_hours.is_finite() {
+            return Err(StudyTimerError::InvalidHours("weekly budget must be more than 0 hours".to_string()));
+        }
+
+        let day_minutes = (SUGGEST_DAY_END_HOUR - SUGGEST_DAY_START_HOUR) * 60;
+        if session_length == 0 || session_length > day_minutes {
+            return Err(StudyTimerError::InvalidDuration(format!("session length must be between 1 and {} minutes", day_minutes)));
+        }
+
+        let mut remaining: Vec<(&str, f32)> = self.config.subjects
+            .iter()
+            .map(|(name, subject)| (name.as_str(), subject.target_hours - subject.completed_hours))
+            .filter(|(_, hours)| *hours > 0.0)
+            .collect();
+        remaining.sort_by(|a, b| a.0.cmp(b.0));
+
+        let total_remaining: f32 = remaining.iter().map(|(_, hours)| hours).sum();
+        let budget_minutes = budget_hours * 60.0;
+
+        // sessions still wanted per subject, after what is already on the schedule
+        let mut wanted: Vec<(&str, u32)> = remaining
+            .iter()
+            .map(|(name, hours)| {
+                let share = (budget_minutes * hours / total_remaining).min(hours * 60.0);
+                let scheduled: u32 = self.config.schedules
+                    .get(*name)
+                    .map(|sessions| sessions.iter().map(|session| session.duration).sum())
+                    .unwrap_or(0);
+                let needed = (share - scheduled as f32).max(0.0);
+
+                (*name, (needed / session_length as f32).round() as u32)
+            })
+            .filter(|(_, sessions)| *sessions > 0)
+            .collect();
+
+        let weekdays: Vec<&str> = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+            .into_iter()
+            .map(Self::day_name)
+            .collect();
+
+        let mut taken: Vec<StudySession> = self.config.schedules.values().flatten().cloned().collect();
+        let mut proposed: Vec<(String, StudySession)> = Vec::new();
+
+        // one session per subject per round keeps the plan balanced when slots run out
+        while wanted.iter().any(|(_, sessions)| *sessions > 0) {
+            let mut placed_any = false;
+
+            for (name, sessions) in wanted.iter_mut().filter(|(_, sessions)| *sessions > 0) {
+                let name: &str = name;
+                let days_used = |day: &str| {
+                    proposed.iter().filter(|(subject, session)| subject == name && session.day == day).count()
+                        + self.config.schedules.get(name).map(|existing| existing.iter().filter(|session| session.day == day).count()).unwrap_or(0)
+                };
+
+                let mut candidates: Vec<StudySession> = weekdays
+                    .iter()
+                    .flat_map(|day| {
+                        (SUGGEST_DAY_START_HOUR * 60..=SUGGEST_DAY_END_HOUR * 60 - session_length)
+                            .step_by(60)
+                            .map(move |start| StudySession::new(day, &format!("{:02}:{:02}", start / 60, start % 60), session_length))
+                    })
+                    .filter(|candidate| !taken.iter().any(|session| session.overlaps(candidate)))
+                    .filter(|candidate| {
+                        NaiveTime::parse_from_str(&candidate.start_time, "%H:%M")
+                            .map(|start| !self.config.overlaps_dnd(start, candidate.duration))
+                            .unwrap_or(false)
+                    })
+                    .collect();
+                candidates.sort_by_key(|candidate| days_used(&candidate.day));
+
+                match candidates.into_iter().next() {
+                    Some(session) => {
+                        taken.push(session.clone());
+                        proposed.push((name.to_string(), session));
+                        *sessions -= 1;
+                        placed_any = true;
+                    },
+                    None => *sessions = 0,
+                }
+            }
+
+            if !placed_any {
+                break;
+            }
+        }
+
+        Ok(proposed)
+    }
+
+    pub fn remove_schedule(&mut self, subject: &str, day: &str, start_time: &str) -> Result<StudySession, StudyTimerError> {
+        let removed = self.config.remove_schedule(subject, day, start_time)?;
+        self.save()?;
+        Ok(removed)
+    }
+
+    pub fn move_schedule(&mut self, subject: &str, day: &str, start_time: &str, new_day: &str, new_start_time: &str) -> Result<(StudySession, StudySession), StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+
+        let moved = self.config.move_schedule(subject, day, start_time, new_day, new_start_time)?;
+        self.save()?;
+
+        // the moved session is appended last
+        let existing = self.config.schedules.get(subject).map(|sessions| sessions.len() - 1).unwrap_or(0);
+        self.warn_conflicts(subject, existing);
+        self.warn_shortfall(subject);
+
+        Ok(moved)
+    }
+
+    pub fn copy_schedule(&mut self, subject: &str, from_day: &str, to_days: &str) -> Result<(Vec<StudySession>, Vec<StudySession>), StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+        let existing = self.config.schedules.get(subject).map(|sessions| sessions.len()).unwrap_or(0);
+
+        let (copied, skipped) = self.config.copy_schedule(subject, from_day, to_days)?;
+        if !copied.is_empty() {
+            self.save()?;
+            self.warn_conflicts(subject, existing);
+            self.warn_shortfall(subject);
+        }
+
+        Ok((copied, skipped))
+    }
+
+    pub fn set_deadline(&mut self, name: &str, deadline: Option<NaiveDate>) -> Result<(), StudyTimerError> {
+        self.config.set_deadline(name, deadline)?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_tags(&mut self, name: &str, tags: &[String]) -> Result<(), StudyTimerError> {
+        self.config.set_tags(name, tags)?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_weekly_target(&mut self, name: &str, weekly_target: f32) -> Result<(), StudyTimerError> {
+        self.config.set_weekly_target(name, weekly_target)?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn rename_subject(&mut self, old: &str, new: &str) -> Result<(), StudyTimerError> {
+        self.config.rename_subject(old, new)?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn remove_subject(&mut self, name: &str) -> Result<usize, StudyTimerError> {
+        let removed_sessions = self.config.remove_subject(name)?;
+        self.save()?;
+        Ok(removed_sessions)
+    }
+
+    // save copies the file on disk to the backup first, so the cleared data can be restored
+    pub fn clear(&mut self) -> Result<(usize, usize), StudyTimerError> {
+        let removed = self.config.clear();
+        self.save()?;
+        Ok(removed)
+    }
+
+    pub fn log_hours(&mut self, subject: &str, hours: f32) -> Result<(), StudyTimerError> {
+        self.config.log_hours(subject, hours)?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), StudyTimerError> {
+        self.config.set(key, value)?;
+        self.save()?;
+        self.notifier = Arc::new(Self::build_notifier(&self.config));
+        Ok(())
+    }
+
+    pub fn list_subjects(&self, tag: Option<&str>, filter: &ListFilter) -> Result<(), StudyTimerError> {
+        let selection = self.list_selection(filter)?;
+
+        println!("{}", "Subjects and schedules:".bold());
+        println!("{}", "-".repeat(50));
+
+        let today = Local::now().date_naive();
+
+        for (name, subject) in self.subjects_by_deadline(tag) {
+            let listed: Option<Vec<&StudySession>> = self.config.schedules
+                .get(name)
+                .map(|sessions| Self::sorted_sessions(sessions).into_iter().filter(|session| selection.shows_session(session)).collect::<Vec<_>>())
+                .filter(|listed| selection.days.is_none() || !listed.is_empty());
+            if !selection.shows_subject(name, listed.is_some()) {
+                continue;
+            }
+
+            println!("{}: {} hours target", name.green().bold(), subject.target_hours);
+            if !subject.tags.is_empty() {
+                println!(" Tags: {}", subject.tags.join(", "));
+            }
+            println!(" Progress: {:.1}/{:.1} hours ({})",
+            subject.completed_hours,
+            subject.target_hours,
+            Self::format_percentage(Self::percentage(subject.completed_hours, subject.target_hours)));
+            println!(" Last studied: {}", Self::format_last_studied(subject.last_studied, Local::now()));
+            if let Some(summary) = Self::format_deadline(subject, today) {
+                println!(" Deadline: {}", summary);
+            }
+            let sessions = self.config.schedules.get(name).map(Vec::as_slice).unwrap_or_default();
+            println!(" Scheduled: {:.1}h/week", Self::weekly_scheduled_hours(sessions));
+            if let Some(warning) = Self::shortfall_warning(subject, sessions, today) {
+                println!(" {} {}", "warning:".yellow().bold(), warning);
+            }
+
+            if let Some(listed) = listed {
+                println!(" Scheduled sessions:");
+                for session in listed {
+                    let lead = session.lead_minutes.map(|lead| format!(", reminder {} minutes before", lead)).unwrap_or_default();
+                    println!("  {} at {}-{} for {} minutes{}",
+                             session.describe_day().blue(),
+                             session.start_time,
+                             session.end_time,
+                             session.duration,
+                             lead);
+                }
+            } else {
+                println!("  No scheduled sessions");
+            }
+            println!();
+        }
+
+        Ok(())
+    }
+
+    // an unknown subject is an error rather than an empty listing, it is most likely a typo
+    fn list_selection(&self, filter: &ListFilter) -> Result<ListSelection<'_>, StudyTimerError> {
+        let subjects = if filter.subjects.is_empty() {
+            None
+        } else {
+            Some(filter.subjects.iter().map(|name| self.config.resolve_subject(name)).collect::<Result<HashSet<_>, _>>()?)
+        };
+
+        let days = if filter.days.is_empty() {
+            None
+        } else {
+            let mut days = Vec::new();
+            for day in &filter.days {
+                days.extend(Config::parse_days(day)?);
+            }
+            Some(days)
+        };
+
+        Ok(ListSelection { subjects, days })
+    }
+
+    // subject_reports narrowed down the same way list_subjects is
+    pub fn list_reports(&self, tag: Option<&str>, filter: &ListFilter) -> Result<Vec<SubjectReport<'_>>, StudyTimerError> {
+        let selection = self.list_selection(filter)?;
+        let mut reports = self.subject_reports(tag);
+
+        for report in &mut reports {
+            report.sessions.retain(|session| selection.shows_session(session));
+        }
+        reports.retain(|report| selection.shows_subject(report.name, !report.sessions.is_empty()));
+
+        Ok(reports)
+    }
+
+    pub fn next_session(&self, now: &NaiveDateTime) -> Option<(&str, &StudySession, i64)> {
+        self.config.schedules
+            .iter()
+            .flat_map(|(subject, sessions)| sessions.iter().map(move |session| (subject.as_str(), session)))
+            .filter_map(|(subject, session)| {
+                Self::minutes_until_session(now, session).map(|minutes| (subject, session, minutes))
+            })
+            .min_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.cmp(b.0)))
+    }
+
+    pub fn next_report(&self, now: &NaiveDateTime) -> Option<NextReport<'_>> {
+        self.next_session(now).map(|(subject, session, minutes_until)| NextReport { subject, session, minutes_until })
+    }
+
+    // a single undecorated line for shell prompts and status bars, colors only reach a terminal
+    pub fn summary_line(&self, now: &NaiveDateTime) -> String {
+        let separator = if self.ascii { " | " } else { " · " };
+
+        let subjects = self.config.subjects.len();
+        let (completed, target) = self.config.subjects
+            .values()
+            .fold((0.0, 0.0), |(completed, target), subject| (completed + subject.completed_hours, target + subject.target_hours));
+        let overall = match Self::percentage(completed, target) {
+            Some(percentage) => format!("{:.0}% overall", percentage),
+            None => "no progress yet".to_string(),
+        };
+
+        let next = match self.next_session(now) {
+            Some((subject, _, 0)) => format!("next: {} now", subject.green()),
+            Some((subject, _, minutes)) => format!("next: {} in {}", subject.green(), Self::format_minutes(minutes)),
+            None => "nothing scheduled".to_string(),
+        };
+
+        let subjects = if subjects == 1 { "1 subject".to_string() } else { format!("{} subjects", subjects) };
+        [subjects, overall, next].join(separator)
+    }
+
+    pub fn show_next(&self) {
+        match self.next_session(&Local::now().naive_local()) {
+            Some((subject, session, minutes)) => {
+                let starts = if minutes == 0 {
+                    "starts now".to_string()
+                } else {
+                    format!("starts in {}", Self::format_minutes(minutes))
+                };
+
+                println!("next: {} on {} at {} for {} minutes ({})",
+                         subject.green().bold(),
+                         session.describe_day().blue(),
+                         session.start_time,
+                         session.duration,
+                         starts);
+            },
+            None => println!("nothing scheduled in the next 7 days"),
+        }
+    }
+
+    pub fn format_minutes(minutes: i64) -> String {
+        let days = minutes / (24 * 60);
+        let hours = minutes % (24 * 60) / 60;
+        let minutes = minutes % 60;
+
+        if days > 0 {
+            format!("{}d{}h{}m", days, hours, minutes)
+        } else if hours > 0 {
+            format!("{}h{}m", hours, minutes)
+        } else {
+            format!("{}m", minutes)
+        }
+    }
+
+    // every subject when no tag is given
+    fn tagged_subjects<'a>(&'a self, tag: Option<&str>) -> impl Iterator<Item = (&'a String, &'a Subject)> {
+        self.config.subjects
+            .iter()
+            .filter(move |(_, subject)| tag.is_none_or(|tag| subject.has_tag(tag)))
+    }
+
+    // completed and target hours summed per tag, a subject counts towards each of its tags
+    fn tag_totals<'a>(subjects: impl Iterator<Item = &'a Subject>) -> Vec<(&'a str, f32, f32)> {
+        let mut totals: BTreeMap<&str, (f32, f32)> = BTreeMap::new();
+
+        for subject in subjects {
+            for tag in &subject.tags {
+                let (completed, target) = totals.entry(tag.as_str()).or_insert((0.0, 0.0));
+                *completed += subject.completed_hours;
+                *target += subject.target_hours;
+            }
+        }
+
+        totals
+            .into_iter()
+            .map(|(tag, (completed, target))| (tag, completed, target))
+            .collect()
+    }
+
+    // sorted by name so scripted output is stable between runs
+    pub fn subject_reports(&self, tag: Option<&str>) -> Vec<SubjectReport<'_>> {
+        let mut reports: Vec<SubjectReport> = self.tagged_subjects(tag)
+            .map(|(name, subject)| SubjectReport {
+                name,
+                target_hours: subject.target_hours,
+                completed_hours: subject.completed_hours,
+                percentage: Self::percentage(subject.completed_hours, subject.target_hours),
+                sessions: self.config.schedules
+                    .get(name)
+                    .map(|sessions| Self::sorted_sessions(sessions))
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        reports.sort_by(|a, b| a.name.cmp(b.name));
+        reports
+    }
+
+    pub fn progress_report(&self, tag: Option<&str>) -> ProgressReport<'_> {
+        let subjects = self.subject_reports(tag);
+        let total_target_hours = subjects.iter().map(|report| report.target_hours).sum();
+        let total_completed_hours = subjects.iter().map(|report| report.completed_hours).sum();
+
+        ProgressReport {
+            subjects,
+            total_target_hours,
+            total_completed_hours,
+            overall_percentage: Self::percentage(total_completed_hours, total_target_hours),
+            balanced_percentage: Self::balanced_percentage(self.tagged_subjects(tag).map(|(_, subject)| subject)),
+        }
+    }
+
+    pub fn print_subjects_json(&self, tag: Option<&str>, filter: &ListFilter) -> Result<(), StudyTimerError> {
+        println!("{}", serde_json::to_string_pretty(&self.list_reports(tag, filter)?)?);
+        Ok(())
+    }
+
+    pub fn print_progress_json(&self, tag: Option<&str>) -> Result<(), StudyTimerError> {
+        println!("{}", serde_json::to_string_pretty(&self.progress_report(tag))?);
+        Ok(())
+    }
+
+    // hours studied per subject within the range, from the history rather than the running totals.
+    // current subjects show up even at zero, removed ones only when they have history and no tag is given
+    pub fn range_hours(&self, from: Option<NaiveDate>, to: Option<NaiveDate>, tag: Option<&str>) -> Result<BTreeMap<String, f32>, StudyTimerError> {
+        if let (Some(from), Some(to)) = (from, to) && from > to {
+            return Err(StudyTimerError::InvalidDate(format!("--from {} is after --to {}", from, to)));
+        }
+
+        let mut hours = History::hours_by_subject(&self.history.read()?, from, to);
+        if tag.is_some() {
+            hours.retain(|name, _| self.tagged_subjects(tag).any(|(subject, _)| subject == name));
+        }
+        for (name, _) in self.tagged_subjects(tag) {
+            hours.entry(name.clone()).or_insert(0.0);
+        }
+
+        Ok(hours)
+    }
+
+    pub fn show_range_progress(&self, from: Option<NaiveDate>, to: Option<NaiveDate>, tag: Option<&str>) -> Result<(), StudyTimerError> {
+        let hours = self.range_hours(from, to, tag)?;
+        let describe = |date: Option<NaiveDate>, open: &str| date.map_or(open.to_string(), |date| date.to_string());
+
+        println!("{}", format!("study progress from {} to {}:", describe(from, "the start"), describe(to, "today")).bold());
+        println!("{}", "-".repeat(50));
+
+        for (name, completed) in &hours {
+            match self.config.subjects.get(name) {
+                Some(subject) => println!("{}: {:.1} hours ({} of target)",
+                                          name.green().bold(),
+                                          completed,
+                                          Self::format_percentage(Self::percentage(*completed, subject.target_hours))),
+                None => println!("{}: {:.1} hours (no longer a subject)", name.green(), completed),
+            }
+        }
+
+        println!("\n{} {:.1} hours", "Total:".bold(), hours.values().sum::<f32>());
+        Ok(())
+    }
+
+    pub fn print_range_progress_json(&self, from: Option<NaiveDate>, to: Option<NaiveDate>, tag: Option<&str>) -> Result<(), StudyTimerError> {
+        let subjects = self.range_hours(from, to, tag)?;
+        let total_hours = subjects.values().sum();
+        println!("{}", serde_json::to_string_pretty(&RangeReport { from, to, subjects, total_hours })?);
+        Ok(())
+    }
+
+    // writes to stdout when no path is given
+    pub fn export(&self, format: ExportFormat, path: Option<&Path>) -> Result<(), StudyTimerError> {
+        let reports = self.subject_reports(None);
+
+        match path {
+            Some(path) => export::write(format, &reports, Local::now(), BufWriter::new(File::create(path)?)),
+            None => export::write(format, &reports, Local::now(), io::stdout().lock()),
+        }
+    }
+
+    // existing subjects are skipped, or with merge get the imported target. saved once at the end
+    pub fn import(&mut self, path: &Path, merge: bool) -> Result<ImportSummary, StudyTimerError> {
+        let rows = import::parse(&std::fs::read_to_string(path)?)?;
+        let mut summary = ImportSummary::default();
+
+        for row in rows {
+            let subject = match row.subject {
+                Ok(subject) => subject,
+                Err(e) => {
+                    summary.failed.push((row.location, e));
+                    continue;
+                },
+            };
+
+            let existing = self.config.resolve_subject(&subject.name).ok().map(str::to_string);
+            let result = match existing {
+                Some(existing) if merge => self.config.edit_target(&existing, subject.target_hours).map(|_| summary.updated.push(existing)),
+                Some(existing) => {
+                    summary.skipped.push(existing);
+                    Ok(())
+                },
+                None => self.config.add_subject(&subject.name, subject.target_hours, 0.0, false).map(|name| summary.added.push(name)),
+            };
+
+            if let Err(e) = result {
+                summary.failed.push((row.location, e.to_string()));
+            }
+        }
+
+        if !summary.added.is_empty() || !summary.updated.is_empty() {
+            self.save()?;
+        }
+
+        Ok(summary)
+    }
+
+    // nearest deadline first, subjects without one follow in name order
+    fn subjects_by_deadline(&self, tag: Option<&str>) -> Vec<(&str, &Subject)> {
+        let mut subjects: Vec<(&str, &Subject)> = self.tagged_subjects(tag)
+            .map(|(name, subject)| (name.as_str(), subject))
+            .collect();
+
+        subjects.sort_by_key(|(name, subject)| (subject.deadline.is_none(), subject.deadline, *name));
+        subjects
+    }
+
+    fn format_deadline(subject: &Subject, today: NaiveDate) -> Option<String> {
+        let deadline = subject.deadline?;
+        let days_left = (deadline - today).num_days();
+        let remaining = subject.target_hours - subject.completed_hours;
+
+        let summary = if remaining <= 0.0 {
+            format!("{}, target reached", deadline)
+        } else if days_left < 0 {
+            format!("{}, {} overdue", deadline, Self::format_days(days_left.unsigned_abs() as u32)).red().to_string()
+        } else {
+            // the deadline day itself still counts as a day to study
+            let per_day = remaining / (days_left + 1) as f32;
+            format!("{}, {} left, {:.1}h/day needed", deadline, Self::format_days(days_left as u32), per_day)
+        };
+
+        Some(summary)
+    }
+
+    pub fn check(&self) -> Vec<String> {
+        self.config.check()
+    }
+
+    // one-off sessions don't repeat, so only weekly ones count towards the week
+    fn weekly_scheduled_hours(sessions: &[StudySession]) -> f32 {
+        sessions
+            .iter()
+            .filter(|session| session.date.is_none())
+            .map(|session| session.duration as f32 / 60.0)
+            .sum()
+    }
+
+    // hours the sessions from today up to and including the deadline leave short of the target,
+    // None without a deadline, once it has passed, or when the schedule is enough
+    fn deadline_shortfall(subject: &Subject, sessions: &[StudySession], today: NaiveDate) -> Option<f32> {
+        let deadline = subject.deadline.filter(|deadline| *deadline >= today)?;
+        let remaining = subject.target_hours - subject.completed_hours;
+
+        let scheduled: f32 = today
+            .iter_days()
+            .take_while(|date| *date <= deadline)
+            .flat_map(|date| sessions.iter().filter(move |session| session.occurs_on(date)))
+            .map(|session| session.duration as f32 / 60.0)
+            .sum();
+
+        (remaining - scheduled > 0.0).then_some(remaining - scheduled)
+    }
+
+    // the weeks left include the deadline day, a deadline this week counts as a whole week
+    fn shortfall_warning(subject: &Subject, sessions: &[StudySession], today: NaiveDate) -> Option<String> {
+        let shortfall = Self::deadline_shortfall(subject, sessions, today)?;
+        let deadline = subject.deadline?;
+        let weeks_left = ((deadline - today).num_days() + 1) as f32 / 7.0;
+
+        Some(format!("scheduled sessions leave {:.1}h of the target undone by {}, about {:.1}h/week more is needed",
+                     shortfall, deadline, shortfall / weeks_left.max(1.0)))
+    }
+
+    fn warn_shortfall(&self, subject: &str) {
+        if self.quiet() {
+            return;
+        }
+
+        let (Some(details), sessions) = (self.config.subjects.get(subject), self.config.schedules.get(subject)) else {
+            return;
+        };
+
+        if let Some(warning) = Self::shortfall_warning(details, sessions.map(Vec::as_slice).unwrap_or_default(), Local::now().date_naive()) {
+            println!("{} {}", "warning:".yellow().bold(), warning);
+        }
+    }
+
+    // orders by weekday, then start time
+    fn sorted_sessions(sessions: &[StudySession]) -> Vec<&StudySession> {
+        let mut sorted: Vec<&StudySession> = sessions.iter().collect();
+        sorted.sort_by_key(|session| session.week_interval().map(|(start, _)| start));
+        sorted
+    }
+
+    pub fn sessions_on(&self, date: NaiveDate) -> Vec<(&str, &StudySession)> {
+        Self::sessions_in(&self.config, date)
+    }
+
+    // the daemon works on its own config snapshot, hence the associated fn
+    fn sessions_in(config: &Config, date: NaiveDate) -> Vec<(&str, &StudySession)> {
+        let mut sessions: Vec<(&str, &StudySession)> = config.schedules
+            .iter()
+            .flat_map(|(subject, sessions)| sessions.iter().map(move |session| (subject.as_str(), session)))
+            .filter(|(_, session)| session.occurs_on(date))
+            .collect();
+
+        sessions.sort_by_key(|(subject, session)| {
+            (NaiveTime::parse_from_str(&session.start_time, "%H:%M").ok(), *subject)
+        });
+
+        sessions
+    }
+
+    pub fn show_today(&self) {
+        let now = self.config.wall_clock(&Local::now());
+        let today = Self::day_name(now.weekday());
+        let sessions = self.sessions_on(now.date());
+
+        if sessions.is_empty() {
+            println!("nothing scheduled today");
+            return;
+        }
+
+        println!("{}", format!("Today ({}):", today).bold());
+        println!("{}", "-".repeat(50));
+
+        for (subject, session) in sessions {
+            let line = format!("  {}-{} {} for {} minutes", session.start_time, session.end_time, subject, session.duration);
+
+            if Self::session_ended(session, now.time()) {
+                println!("{}", line.dimmed());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+
+    // the morning agenda, the same sessions the today command lists
+    fn digest_message(config: &Config, date: NaiveDate) -> Option<String> {
+        let sessions = Self::sessions_in(config, date);
+        if sessions.is_empty() {
+            return config.digest_empty_days.then(|| "nothing scheduled today".to_string());
+        }
+
+        let lines: Vec<String> = sessions
+            .iter()
+            .map(|(subject, session)| format!("{}-{} {}", session.start_time, session.end_time, subject))
+            .collect();
+        let plural = if sessions.len() == 1 { "" } else { "s" };
+
+        Some(format!("{} session{} today\n{}", sessions.len(), plural, lines.join("\n")))
+    }
+
+    // a session running past midnight hasn't ended yet today
+    fn session_ended(session: &StudySession, now: NaiveTime) -> bool {
+        NaiveTime::parse_from_str(&session.start_time, "%H:%M")
+            .map(|start| {
+                let (end, wrapped) = start.overflowing_add_signed(chrono::Duration::minutes(session.duration as i64));
+                wrapped == 0 && end <= now
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn dashboard(&self, now: &NaiveDateTime) -> Result<Dashboard, StudyTimerError> {
+        let mut names: Vec<&String> = self.config.subjects.keys().collect();
+        names.sort();
+
+        let subjects = names
+            .into_iter()
+            .map(|name| {
+                let subject = &self.config.subjects[name];
+                let percentage = Self::percentage(subject.completed_hours, subject.target_hours);
+                DashboardSubject {
+                    name: name.clone(),
+                    completed_hours: subject.completed_hours,
+                    target_hours: subject.target_hours,
+                    percentage,
+                    bar: self.progress_bar(percentage.unwrap_or(0.0)),
+                }
+            })
+            .collect();
+
+        let today = self.sessions_on(now.date())
+            .into_iter()
+            .map(|(subject, session)| (subject.to_string(), session.clone(), Self::session_ended(session, now.time())))
+            .collect();
+
+        // next_session counts whole minutes from the start of this one, the seconds make it tick
+        let next = self.next_session(now).map(|(subject, session, minutes)| {
+            let seconds = if minutes == 0 { 0 } else { minutes * 60 - now.second() as i64 };
+            (subject.to_string(), session.clone(), seconds)
+        });
+
+        Ok(Dashboard {
+            daemon: self.daemon_state()?,
+            paused: self.config.is_paused(now.date()),
+            subjects,
+            today,
+            next,
+        })
+    }
+
+    fn day_name(weekday: Weekday) -> &'static str {
+        match weekday {
+            Weekday::Mon => "Monday",
+            Weekday::Tue => "Tuesday",
+            Weekday::Wed => "Wednesday",
+            Weekday::Thu => "Thursday",
+            Weekday::Fri => "Friday",
+            Weekday::Sat => "Saturday",
+            Weekday::Sun => "Sunday",
+        }
+    }
+
+    pub async fn focus(&mut self, subject: &str, duration: u32) -> Result<f32, StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+
+        if duration == 0 {
+            return Err(StudyTimerError::InvalidDuration("session duration must be at least 1 minute".to_string()));
+        }
+
+        // starting over would overwrite the interrupted session and lose its minutes
+        if let Some(state) = self.session_file.load()? {
+            return Err(StudyTimerError::Session(format!("an interrupted focus session on '{}' from {} is waiting, run 'study_timer resume-focus' first",
+                                                        state.subject, state.start_time.format("%Y-%m-%d %H:%M"))));
+        }
+
+        let mut schedule = Schedule::new();
+        schedule.start_session(subject, duration)?;
+
+        self.run_focus(schedule, duration).await
+    }
+
+    // picks up the session a closed terminal or crash left behind, the time it was down isn't credited
+    pub async fn resume_focus(&mut self) -> Result<(String, f32), StudyTimerError> {
+        let state = self.session_file.load()?
+            .ok_or_else(|| StudyTimerError::Session("no interrupted focus session to resume".to_string()))?;
+        let subject = state.subject.clone();
+        let duration = (state.end_time - state.start_time).num_minutes().max(0) as u32;
+
+        let mut schedule = Schedule::new();
+        schedule.restore(state, Local::now());
+
+        let hours = self.run_focus(schedule, duration).await?;
+        Ok((subject, hours))
+    }
+
+    async fn run_focus(&mut self, mut schedule: Schedule, duration: u32) -> Result<f32, StudyTimerError> {
+        let subject = schedule.get_current_session()
+            .map(|session| session.subject.clone())
+            .ok_or_else(|| StudyTimerError::Session("no active focus session".to_string()))?;
+
+        let mut toggles = Self::pause_toggles();
+        let interrupted = Self::countdown(&mut schedule, &subject.green().bold().to_string(), Some(&self.session_file), &mut toggles).await?;
+
+        let session = schedule.end_session().ok_or_else(|| StudyTimerError::Session("no active focus session".to_string()))?;
+        let hours = Self::studied_hours(&session);
+        self.log_hours(&subject, hours)?;
+        self.record_history(&session);
+
+        // only once the hours are logged, a failure before this leaves the session to resume
+        if let Err(e) = self.session_file.remove() {
+            eprintln!("failed to remove {}: {}", self.session_file.path().display(), e);
+        }
+
+        if !interrupted {
+            let message = format!("{} focus session done, {} minutes logged", subject, duration);
+            self.notify_or_warn("Study Timer", &message);
+        }
+
+        Ok(hours)
+    }
+
+    pub async fn pomodoro(&mut self, subject: &str, work: u32, break_minutes: u32, rounds: u32) -> Result<f32, StudyTimerError> {
+        let subject = &self.config.resolve_subject(subject)?.to_string();
+
+        if work == 0 {
+            return Err(StudyTimerError::InvalidDuration("work interval must be at least 1 minute".to_string()));
+        }
+
+        if rounds == 0 {
+            return Err(StudyTimerError::InvalidDuration("pomodoro needs at least 1 round".to_string()));
+        }
+
+        let mut schedule = Schedule::new();
+        let mut toggles = Self::pause_toggles();
+        let mut total_hours = 0.0;
+
+        for round in 1..=rounds {
+            if !self.quiet() {
+                println!("{} round {}/{}: {} minutes of {}", "work".green().bold(), round, rounds, work, subject);
+            }
+            schedule.start_session(subject, work)?;
+
+            let label = format!("round {}/{} {}", round, rounds, "work".green().bold());
+            let interrupted = Self::countdown(&mut schedule, &label, None, &mut toggles).await?;
+
+            // only work intervals count towards completed hours
+            let session = schedule.end_session().ok_or_else(|| StudyTimerError::Session("no active pomodoro session".to_string()))?;
+            let hours = Self::studied_hours(&session);
+            self.log_hours(subject, hours)?;
+            self.record_history(&session);
+            total_hours += hours;
+
+            if interrupted {
+                return Ok(total_hours);
+            }
+
+            if round == rounds {
+                let message = format!("{} pomodoro done, {} rounds completed", subject, rounds);
+                self.notify_or_warn("Study Timer", &message);
+                break;
+            }
+
+            if break_minutes == 0 {
+                continue;
+            }
+
+            let message = format!("round {}/{} done, take a {} minute break", round, rounds, break_minutes);
+            self.notify_or_warn("Study Timer", &message);
+
+            if !self.quiet() {
+                println!("{} round {}/{}: {} minutes", "break".blue().bold(), round, rounds, break_minutes);
+            }
+            schedule.start_session(subject, break_minutes)?;
+
+            let label = format!("round {}/{} {}", round, rounds, "break".blue().bold());
+            let interrupted = Self::countdown(&mut schedule, &label, None, &mut toggles).await?;
+            schedule.end_session();
+
+            if interrupted {
+                return Ok(total_hours);
+            }
+
+            let message = format!("break over, round {}/{} of {} starting", round + 1, rounds, subject);
+            self.notify_or_warn("Study Timer", &message);
+        }
+
+        Ok(total_hours)
+    }
+
+    // one message per Enter press. read on a plain thread, tokio's stdin would hold up exit until the next line
+    fn pause_toggles() -> mpsc::UnboundedReceiver<()> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if io::stdin().is_terminal() {
+            thread::spawn(move || {
+                for line in io::stdin().lock().lines() {
+                    if line.is_err() || sender.send(()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        receiver
+    }
+
+    // redraws the remaining time of the active session in place, returns true when cut short with Ctrl-C.
+    // each toggle pauses or resumes the session. with a session file the session is saved every tick,
+    // so it can be resumed if the process dies
+    async fn countdown(schedule: &mut Schedule, label: &str, session_file: Option<&SessionFile>, toggles: &mut mpsc::UnboundedReceiver<()>) -> Result<bool, StudyTimerError> {
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        let mut save_failed = false;
+
+        let interrupted = loop {
+            if let (Some(session_file), Some(state)) = (session_file.filter(|_| !save_failed), schedule.snapshot(Local::now())) {
+                // the countdown itself still works, it just can't be resumed
+                if let Err(e) = session_file.save(&state) {
+                    eprintln!("\nfailed to save the session to {}: {}", session_file.path().display(), e);
+                    save_failed = true;
+                }
+            }
+
+            let remaining = schedule.time_remaining().unwrap_or_else(chrono::Duration::zero);
+            let paused = if schedule.is_paused() { "(paused)" } else { "" };
+            print!("\r{} {} remaining {:<8} ", label, Self::format_countdown(remaining), paused);
+            io::stdout().flush()?;
+
+            if remaining <= chrono::Duration::zero() {
+                break false;
+            }
+
+            tokio::select! {
+                _ = time::sleep(Duration::from_secs(1)) => {},
+                _ = &mut ctrl_c => break true,
+                Some(()) = toggles.recv() => {
+                    if schedule.is_paused() {
+                        schedule.resume()?;
+                    } else {
+                        schedule.pause()?;
+                    }
+                },
+            }
+        };
+        println!();
+
+        Ok(interrupted)
+    }
+
+    fn studied_hours(session: &crate::schedule::StudySession) -> f32 {
+        session.studied.num_seconds().max(0) as f32 / 3600.0
+    }
+
+    // the hours are already logged at this point, a failed history write shouldn't fail the session
+    fn record_history(&self, session: &crate::schedule::StudySession) {
+        let minutes = session.studied.num_seconds().max(0) as f32 / 60.0;
+        let entry = HistoryEntry::new(&session.subject, session.start_time, Local::now(), minutes);
+
+        if let Err(e) = self.history.append(&entry) {
+            eprintln!("failed to write session history to {}: {}", self.history.path().display(), e);
+        }
+    }
+
+    pub fn show_stats(&self) -> Result<(), StudyTimerError> {
+        let today = Local::now().date_naive();
+        let since = today - chrono::Duration::days(STATS_DAYS - 1);
+
+        let entries = self.history.read()?;
+        let stats = match Stats::from_entries(&entries, since) {
+            Some(stats) => stats,
+            None => {
+                println!("no study sessions in the last {} days", STATS_DAYS);
+                return Ok(());
+            }
+        };
+
+        println!("{}", format!("last {} days ({} to {}):", STATS_DAYS, since.format("%b %d"), today.format("%b %d")).bold());
+        println!("{}", "-".repeat(50));
+        // pad before coloring, escape codes would otherwise count towards the width
+        let row = |label: &str, value: String| println!("{} {:>10}", format!("{:<20}", label).bold(), value);
+        row("total", Self::format_minutes(stats.total_minutes.round() as i64));
+        row("sessions", stats.sessions.to_string());
+        row("average session", Self::format_minutes(stats.average_minutes.round() as i64));
+        row("busiest day", format!("{} ({})", stats.busiest_day.0.format("%a %b %d"), Self::format_minutes(stats.busiest_day.1.round() as i64)));
+
+        // the week may have begun before the window, so it is counted from the full history
+        let week_minutes = Stats::from_entries(&entries, self.config.current_week_start(today))
+            .map(|stats| stats.total_minutes)
+            .unwrap_or(0.0);
+        row("this week", Self::format_minutes(week_minutes.round() as i64));
+
+        println!("\n{}", "per subject:".bold());
+        for (subject, minutes) in &stats.per_subject {
+            println!("{} {:>10}", format!("{:<20}", subject).green(), Self::format_minutes(minutes.round() as i64));
+        }
+
+        Ok(())
+    }
+
+    pub fn show_history(&self, subject: Option<&str>, since: Option<NaiveDate>, limit: usize) -> Result<(), StudyTimerError> {
+        let entries = History::filter(self.history.read()?, subject, since);
+
+        if entries.is_empty() {
+            println!("no study sessions recorded yet");
+            return Ok(());
+        }
+
+        let skip = entries.len().saturating_sub(limit);
+        for entry in &entries[skip..] {
+            println!("{} {}-{} {} ({})",
+                     entry.start.format("%Y-%m-%d").to_string().blue(),
+                     entry.start.format("%H:%M"),
+                     entry.end.format("%H:%M"),
+                     entry.subject.green().bold(),
+                     Self::format_minutes(entry.minutes.round() as i64));
+        }
+
+        Ok(())
+    }
+
+    // goes through the configured backend, so it fails the same way a real reminder would
+    pub fn test_notification(&self) -> Result<(), StudyTimerError> {
+        self.notifier
+            .notify("Study Timer", "test notification", None)
+            .map_err(|e| StudyTimerError::Notification(e.to_string()))
+    }
+
+    fn notify_or_warn(&self, title: &str, message: &str) {
+        if let Err(e) = self.notifier.notify(title, message, None) {
+            eprintln!("failed to show notification: {}", e);
+        }
+    }
+
+    fn format_countdown(remaining: chrono::Duration) -> String {
+        let seconds = remaining.num_seconds().max(0);
+
+        if seconds >= 3600 {
+            format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+        } else {
+            format!("{:02}:{:02}", seconds / 60, seconds % 60)
+        }
+    }
+
+    pub async fn run_daemon(&mut self) -> Result<JoinHandle<()>, StudyTimerError> {
+        if let Some(pid) = self.pid_file.read()? && pid != std::process::id() && daemon::is_process_alive(pid) {
+            return Err(StudyTimerError::Daemon(format!("daemon is already running (pid {})", pid)));
+        }
+        self.pid_file.write()?;
+
+        self.running.store(true, Ordering::SeqCst);
+        let running = Arc::clone(&self.running);
+        let stop = Arc::clone(&self.stop);
+        let pid_file = self.pid_file.clone();
+
+        let mut config = self.config.clone();
+
+        let notifier = Arc::clone(&self.notifier);
+        let history = self.history.clone();
+        let metrics = Arc::clone(&self.metrics);
+        let clock = Arc::clone(&self.clock);
+
+        let handle = task::spawn(async move {
+            info!(config = %config.config_path.display(), "study timer daemon started");
+
+            let mut daemon_notifier = DaemonNotifier::new(notifier, pid_file.clone(), metrics);
+            let mut notify = |title: &str, message: &str, urgency: Option<Urgency>| daemon_notifier.send(title, message, urgency);
+
+            let mut credited_minute = String::new();
+            // subject and start of each session occurrence, a wide tick range can end two of one subject's sessions
+            let mut credited: HashSet<(String, NaiveDateTime)> = HashSet::new();
+            let mut neglect_reminded: HashMap<String, NaiveDate> = HashMap::new();
+            let mut recently_notified: HashMap<NotificationKey, DateTime<Local>> = HashMap::new();
+            let mut digest_sent: Option<NaiveDate> = None;
+            let mut held_digest: Option<(NaiveDate, ())> = None;
+            let mut held_catch_up: Option<(NaiveDate, String)> = None;
+            let mut covered: Option<NaiveDateTime> = None;
+            let mut last_tick = pid_file.read_last_tick();
+
+            while running.load(Ordering::SeqCst) {
+                let now = clock();
+
+                // config I/O goes through tokio::fs so a slow disk doesn't stall the runtime
+                async {
+                    // a pass can run twice within one minute, only credit each session once
+                    let minute_key = now.format("%Y-%m-%d %H:%M").to_string();
+                    if minute_key != credited_minute {
+                        credited.clear();
+                        credited_minute = minute_key;
+                    }
+
+                    // other commands save while the daemon runs, so take a fresh snapshot every pass.
+                    // a failed read, e.g. a half-written file, keeps the previous one
+                    match Config::load_async(&config.config_path).await {
+                        Ok(on_disk) => config = on_disk,
+                        Err(e) => warn!(error = %e, "failed to reload config, keeping the previous one"),
+                    }
+                    let schedules = config.schedules.clone();
+                    let neglect_days = config.neglect_days;
+
+                    // after a sleep the minutes in between aren't replayed, they get a single catch-up instead
+                    let away_since = last_tick.filter(|last| now - *last > chrono::Duration::minutes(SLEEP_GAP_MINUTES));
+                    if away_since.is_some() {
+                        covered = None;
+                    }
+
+                    // everything below runs on the configured zone's wall clock
+                    let (from, to) = Self::tick_range(covered, config.wall_clock(&now));
+                    covered = Some(to);
+                    let today = to.date();
+
+                    // a paused schedule isn't being studied, so sessions are neither announced nor credited
+                    let paused = config.is_paused(today);
+
+                    if config.reset_weekly(today) {
+                        info!("new week, weekly progress reset");
+                        if let Err(e) = config.save_async().await {
+                            error!(error = %e, "failed to save the weekly reset");
+                        }
+                    }
+
+                    // one-off sessions that have passed are dropped rather than left to pile up
+                    let expired = config.remove_expired_sessions(to);
+                    if expired > 0 {
+                        info!(expired, "removed past one-off sessions");
+                        if let Err(e) = config.save_async().await {
+                            error!(error = %e, "failed to remove past one-off sessions");
+                        }
+                    }
+
+                    // only the popups are held back, sessions are still credited
+                    let do_not_disturb = config.in_dnd(to.time());
+                    debug!(paused, do_not_disturb, %from, %to, "checking sessions");
+
+                    recently_notified.retain(|_, at| now - *at < chrono::Duration::minutes(NOTIFY_DEDUPE_MINUTES));
+
+                    let mut catch_up = None;
+                    if let Some(away_since) = away_since {
+                        let since = config.wall_clock(&away_since.max(now - chrono::Duration::hours(CATCH_UP_HOURS)));
+                        let missed = Self::missed_sessions(&config, &since, &from);
+                        info!(%since, missed = missed.len(), "daemon was away, checking for missed sessions");
+
+                        if !missed.is_empty() && !paused {
+                            catch_up = Some(Self::missed_message(&missed));
+                        }
+                    }
+                    if let Some(message) = Self::hold_for_dnd(&mut held_catch_up, catch_up, today, do_not_disturb || paused) {
+                        notify("Study Timer", &message, None);
+                    }
+
+                    last_tick = Some(now);
+                    if let Err(e) = pid_file.write_last_tick(&now) {
+                        warn!(error = %e, "failed to record the daemon's last pass");
+                    }
+
+                    // sent alongside the per-session reminders, not instead of them
+                    let digest_due = config.digest_time.as_deref()
+                        .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok())
+                        .is_some_and(|time| Self::within(today.and_time(time), &from, &to));
+                    let digest_due = !paused && digest_due && digest_sent != Some(today);
+                    if digest_due {
+                        digest_sent = Some(today);
+                    }
+                    // built when it goes out, a held digest reflects the schedule as it is by then
+                    if Self::hold_for_dnd(&mut held_digest, digest_due.then_some(()), today, do_not_disturb || paused).is_some() && let Some(message) = Self::digest_message(&config, today) {
+                        info!("sending the daily digest");
+                        notify("Study Timer", &message, None);
+                    }
+
+                    for (subject_name, sessions) in schedules.iter().filter(|_| !paused) {
+                        for session in sessions {
+                            let lead_minutes = session.lead(config.reminder_lead_minutes);
+                            if Self::session_starts_within(session, &from, &to)
+                                && Self::first_notification(&mut recently_notified, Self::notification_key("start", subject_name, session), now) {
+                                info!(subject = %subject_name, start = %session.start_time, duration = session.duration, "session started");
+
+                                if !do_not_disturb {
+                                    let message = Config::render_template(&config.reminder_template, subject_name, session, lead_minutes);
+                                    notify("Study Timer", &message, Self::deadline_urgency(&config, subject_name, today));
+                                }
+                            }
+
+                            // hours are credited once the session is over, the end notification confirms it
+                            if let Some(ended) = Self::session_ends_within(session, &from, &to)
+                                && credited.insert((subject_name.clone(), ended - chrono::Duration::minutes(session.duration as i64))) {
+                                info!(subject = %subject_name, start = %session.start_time, duration = session.duration, "session ended");
+
+                                let hours = session.duration as f32 / 60.0;
+                                let saved = match config.log_hours(subject_name, hours) {
+                                    Ok(()) => config.save_async().await,
+                                    Err(e) => Err(e),
+                                };
+                                let message = match &saved {
+                                    Ok(()) => {
+                                        info!(subject = %subject_name, hours, "credited hours");
+                                        format!("{} session done, {} minutes logged", subject_name, session.duration)
+                                    },
+                                    Err(e) => {
+                                        error!(subject = %subject_name, hours, error = %e, "failed to credit hours");
+                                        format!("{} session done, but its {} minutes couldn't be logged", subject_name, session.duration)
+                                    },
+                                };
+
+                                // the scheduled end rather than now, which can be a little past it or in another zone
+                                let end = config.local_from_wall_clock(ended).unwrap_or(now);
+                                let start = end - chrono::Duration::minutes(session.duration as i64);
+                                if let Err(e) = history.append(&HistoryEntry::new(subject_name, start, end, session.duration as f32)) {
+                                    error!(path = %history.path().display(), error = %e, "failed to write session history");
+                                }
+
+                                if !do_not_disturb && Self::first_notification(&mut recently_notified, Self::notification_key("end", subject_name, session), now) {
+                                    notify("Study Timer", &message, None);
+                                }
+                            }
+                            let lead = chrono::Duration::minutes(lead_minutes as i64);
+                            if !do_not_disturb && lead_minutes > 0 && Self::session_starts_within(session, &(from + lead), &(to + lead))
+                                && Self::first_notification(&mut recently_notified, Self::notification_key("pre-reminder", subject_name, session), now) {
+                                let message = Config::render_template(&config.pre_reminder_template, subject_name, session, lead_minutes);
+                                notify("study timer", &message, Self::deadline_urgency(&config, subject_name, today));
+                            }
+                        }
+                    }
+
+                    // held back rather than dropped, it goes out once the window ends
+                    let neglected = if do_not_disturb || paused { Vec::new() } else { Self::neglected_subjects(&config.subjects, neglect_days, now) };
+                    for (subject_name, days) in neglected {
+                        if neglect_reminded.get(subject_name) != Some(&today) {
+                            let message = format!("you haven't studied {} in {} days", subject_name, days);
+                            notify("Study Timer", &message, Self::deadline_urgency(&config, subject_name, today));
+                            neglect_reminded.insert(subject_name.to_string(), today);
+                        }
+                    }
+                }
+                .instrument(info_span!("pass", at = %now.format("%Y-%m-%d %H:%M")))
+                .await;
+
+                // recompute now, the pass above may have straddled a minute boundary
+                let tick = Self::duration_until_next_minute(&clock());
+
+                tokio::select! {
+                    _ = time::sleep(tick) => {},
+                    _ = stop.notified() => {},
+                    _ = daemon::shutdown_signal() => {
+                        info!("shutdown signal received");
+                        running.store(false, Ordering::SeqCst);
+                    },
+                }
+            }
+
+            if let Ok(Some(pid)) = pid_file.read() && pid == std::process::id() {
+                let _ = pid_file.remove();
+            }
+
+            info!("study timer daemon stopped");
+        });
+
+        Ok(handle)
+    }
+
+    // minutes from now until the next occurrence of the session, which may be later today,
+    // tomorrow (across midnight) or up to a week ahead
+    fn minutes_until_session(now: &NaiveDateTime, session: &StudySession) -> Option<i64> {
+        let weekday = session.day.parse::<Weekday>().ok()?;
+        let start_time = NaiveTime::parse_from_str(&session.start_time, "%H:%M").ok()?;
+        let now = now.date().and_hms_opt(now.hour(), now.minute(), 0)?;
+
+        // a one-off session has no next occurrence once it has started
+        if let Some(date) = session.date {
+            let start = date.and_time(start_time);
+            return (start >= now).then(|| start.signed_duration_since(now).num_minutes());
+        }
+
+        let days_ahead = (weekday.num_days_from_monday() + 7 - now.weekday().num_days_from_monday()) % 7;
+        let mut next = (now.date() + chrono::Duration::days(days_ahead as i64)).and_time(start_time);
+        if next < now {
+            next += chrono::Duration::days(7);
+        }
+
+        Some(next.signed_duration_since(now).num_minutes())
+    }
+
+    // the wall-clock minutes a daemon pass covers, after `from` up to and including `to`.
+    // minutes a spring-forward skips are covered by the first minute after the gap, and the hour a fall-back
+    // repeats covers nothing, so every session fires exactly once. a bigger jump starts afresh
+    fn tick_range(covered: Option<NaiveDateTime>, now: NaiveDateTime) -> (NaiveDateTime, NaiveDateTime) {
+        let to = now.with_second(0).and_then(|to| to.with_nanosecond(0)).unwrap_or(now);
+
+        match covered {
+            Some(covered) if (to - covered).num_minutes().abs() <= MAX_CLOCK_SHIFT_MINUTES => (covered, to.max(covered)),
+            _ => (to - chrono::Duration::minutes(1), to),
+        }
+    }
+
+    // today's sessions that started in the window, not yesterday's however recent
+    fn missed_sessions<'a>(config: &'a Config, from: &NaiveDateTime, to: &NaiveDateTime) -> Vec<(&'a str, &'a StudySession)> {
+        Self::sessions_in(config, to.date())
+            .into_iter()
+            .filter(|(_, session)| {
+                NaiveTime::parse_from_str(&session.start_time, "%H:%M")
+                    .is_ok_and(|start| Self::within(to.date().and_time(start), from, to))
+            })
+            .collect()
+    }
+
+    fn missed_message(missed: &[(&str, &StudySession)]) -> String {
+        let sessions: Vec<String> = missed.iter().map(|(subject, session)| format!("{} at {}", subject, session.start_time)).collect();
+        format!("you missed {}", sessions.join(", "))
+    }
+
+    fn within(at: NaiveDateTime, from: &NaiveDateTime, to: &NaiveDateTime) -> bool {
+        *from < at && at <= *to
+    }
+
+    fn session_starts_within(session: &StudySession, from: &NaiveDateTime, to: &NaiveDateTime) -> bool {
+        let Ok(start_time) = NaiveTime::parse_from_str(&session.start_time, "%H:%M") else {
+            return false;
+        };
+
+        [from.date(), to.date()]
+            .into_iter()
+            .filter(|date| session.occurs_on(*date))
+            .any(|date| Self::within(date.and_time(start_time), from, to))
+    }
+
+    // a notice that comes due during do-not-disturb is held until the window ends rather than dropped.
+    // it is about today's sessions, so one still held when the day is over isn't sent at all
+    fn hold_for_dnd<T>(held: &mut Option<(NaiveDate, T)>, due: Option<T>, today: NaiveDate, quiet: bool) -> Option<T> {
+        if let Some(notice) = due {
+            if quiet {
+                info!("do not disturb is on, holding the notification until it ends");
+            }
+            *held = Some((today, notice));
+        }
+
+        match held.take() {
+            Some((day, _)) if day != today => {
+                info!(%day, "dropping a notification held back by do not disturb, its day is over");
+                None
+            },
+            Some((_, notice)) if !quiet => Some(notice),
+            still_held => {
+                *held = still_held;
+                None
+            },
+        }
+    }
+
+    // the end that fell in the window, a session running past midnight started on the day before it ends
+    fn session_ends_within(session: &StudySession, from: &NaiveDateTime, to: &NaiveDateTime) -> Option<NaiveDateTime> {
+        let start_time = NaiveTime::parse_from_str(&session.start_time, "%H:%M").ok()?;
+        let duration = chrono::Duration::minutes(session.duration as i64);
+        let day = chrono::Duration::days(1);
+
+        [from.date() - day, from.date(), to.date() - day, to.date()]
+            .into_iter()
+            .filter(|date| session.occurs_on(*date))
+            .map(|date| date.and_time(start_time) + duration)
+            .find(|end| Self::within(*end, from, to))
+    }
+
+    fn duration_until_next_minute(now: &DateTime<Local>) -> Duration {
+        let elapsed_ms = now.second() as u64 * 1000 + (now.nanosecond() / 1_000_000) as u64;
+        Duration::from_millis(60_000u64.saturating_sub(elapsed_ms).max(1))
+    }
+
+    // a close or missed deadline makes the reminder critical, otherwise the configured urgency applies
+    fn deadline_urgency(config: &Config, subject: &str, today: NaiveDate) -> Option<Urgency> {
+        let deadline = config.subjects.get(subject)?.deadline?;
+        ((deadline - today).num_days() <= URGENT_DEADLINE_DAYS).then_some(Urgency::Critical)
+    }
+
+    fn notification_key(kind: &'static str, subject: &str, session: &StudySession) -> NotificationKey {
+        (kind, subject.to_string(), session.day.clone(), session.start_time.clone())
+    }
+
+    // records the notification, false when the same one already went out within the dedupe window
+    fn first_notification(recent: &mut HashMap<NotificationKey, DateTime<Local>>, key: NotificationKey, now: DateTime<Local>) -> bool {
+        if let Some(at) = recent.get(&key) && now - *at < chrono::Duration::minutes(NOTIFY_DEDUPE_MINUTES) {
+            debug!(subject = %key.1, start = %key.3, "skipping duplicate notification");
+            return false;
+        }
+
+        recent.insert(key, now);
+        true
+    }
+
+    // subjects that were never studied have nothing to measure from, and finished ones are left alone
+    fn neglected_subjects(subjects: &HashMap<String, Subject>, neglect_days: u32, now: DateTime<Local>) -> Vec<(&str, i64)> {
+        if neglect_days == 0 {
+            return Vec::new();
+        }
+
+        let mut neglected: Vec<(&str, i64)> = subjects
+            .iter()
+            .filter(|(_, subject)| subject.completed_hours < subject.target_hours)
+            .filter_map(|(name, subject)| {
+                let days = (now - subject.last_studied?).num_days();
+                (days > neglect_days as i64).then_some((name.as_str(), days))
+            })
+            .collect();
+
+        neglected.sort();
+        neglected
+    }
+
+    pub fn stop_daemon(&self) -> Result<(), StudyTimerError> {
+        self.running.store(false, Ordering::SeqCst);
+        self.stop.notify_one();
+
+        let pid = match self.pid_file.read()? {
+            Some(pid) => pid,
+            None => {
+                println!("no running daemon found");
+                return Ok(());
+            }
+        };
+
+        // the daemon lives in this process, flipping the flag is enough
+        if pid == std::process::id() {
+            println!("sent stop signal to daemon");
+            return Ok(());
+        }
+
+        if !daemon::is_process_alive(pid) {
+            self.pid_file.remove()?;
+            println!("daemon (pid {}) was not running, removed stale pid file {}", pid, self.pid_file.path().display());
+            return Ok(());
+        }
+
+        daemon::terminate(pid)?;
+        println!("sent stop signal to daemon (pid {})", pid);
+        Ok(())
+    }
+
+    pub fn pause(&mut self, until: Option<NaiveDate>) -> Result<(), StudyTimerError> {
+        self.config.pause(until, Local::now().date_naive())?;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<(), StudyTimerError> {
+        self.config.resume();
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn daemon_state(&self) -> Result<DaemonState, StudyTimerError> {
+        Ok(match self.pid_file.read()? {
+            Some(pid) if daemon::is_process_alive(pid) => DaemonState::Running { pid, since: self.pid_file.started_at() },
+            Some(pid) => DaemonState::Stale(pid),
+            None => DaemonState::Stopped,
+        })
+    }
+
+    pub fn show_status(&self) -> Result<(), StudyTimerError> {
+        match self.daemon_state()? {
+            DaemonState::Running { pid, since: Some(since) } => println!("daemon: {} (pid {}) since {}", "running".green().bold(), pid, since.format("%Y-%m-%d %H:%M")),
+            DaemonState::Running { pid, since: None } => println!("daemon: {} (pid {})", "running".green().bold(), pid),
+            DaemonState::Stale(pid) => {
+                println!("daemon: {} (stale pid file for pid {} at {})", "not running".red().bold(), pid, self.pid_file.path().display());
+                println!("  run 'study_timer stop' to clean it up");
+            },
+            DaemonState::Stopped => println!("daemon: {}", "not running".red().bold()),
+        }
+
+        let health = self.pid_file.read_health();
+        if health.failed_notifications > 0 {
+            println!("notifications: {} failed, last error: {}",
+                     health.failed_notifications.to_string().red().bold(),
+                     health.last_error.as_deref().unwrap_or("unknown"));
+            if health.consecutive_failures >= NOTIFY_FALLBACK_AFTER {
+                println!("  the last {} failed in a row, reminders are going to the daemon's terminal or log", health.consecutive_failures);
+            }
+        }
+
+        if self.config.is_paused(Local::now().date_naive()) {
+            match self.config.paused_until {
+                Some(until) => println!("reminders: {} until {}", "paused".yellow().bold(), until),
+                None => println!("reminders: {} until 'study_timer resume'", "paused".yellow().bold()),
+            }
+        }
+
+        self.show_next();
+
+        Ok(())
+    }
+
+    pub fn show_progress(&self, balanced: bool, tag: Option<&str>) {
+        println!("{}", "study progress:".bold());
+        println!("{}", "-".repeat(50));
+
+        let mut total_completed = 0.0;
+        let mut total_target = 0.0;
+        let mut total_scheduled = 0.0;
+
+        for (name, subject) in self.tagged_subjects(tag) {
+            total_completed += subject.completed_hours;
+            total_target += subject.target_hours;
+
+            let percentage = Self::percentage(subject.completed_hours, subject.target_hours);
+            let progress_bar = self.generate_progress_bar(percentage.unwrap_or(0.0));
+
+            println!("{}: {:.1}/{:.1} hours", name.green().bold(), subject.completed_hours, subject.target_hours);
+            match Self::format_overachievement(subject) {
+                Some(marker) => println!("{} {} {}", progress_bar, Self::format_percentage(percentage), marker.cyan().bold()),
+                None => println!("{} {}", progress_bar, Self::format_percentage(percentage)),
+            }
+            if let Some(weekly_target) = subject.weekly_target {
+                let weekly_completed = self.config.weekly_completed(subject, Local::now().date_naive());
+                println!("this week {:.1}/{:.1} hours ({})",
+                         weekly_completed,
+                         weekly_target,
+                         Self::format_percentage(Self::percentage(weekly_completed, weekly_target)));
+            }
+            let sessions = self.config.schedules.get(name).map(Vec::as_slice).unwrap_or_default();
+            if let Some(summary) = Self::format_deadline(subject, Local::now().date_naive()) {
+                println!("deadline {}", summary);
+            }
+            if let Some(warning) = Self::shortfall_warning(subject, sessions, Local::now().date_naive()) {
+                println!("{} {}", "warning:".yellow().bold(), warning);
+            }
+            total_scheduled += Self::weekly_scheduled_hours(sessions);
+        }
+
+        let (label, overall_percentage) = if balanced {
+            ("Overall progress (each subject counts equally):", Self::balanced_percentage(self.tagged_subjects(tag).map(|(_, subject)| subject)))
+        } else {
+            ("Overall progress (weighted by target hours):", Self::percentage(total_completed, total_target))
+        };
+        println!("\n{}", label.bold());
+        let overall_bar = self.generate_progress_bar(overall_percentage.unwrap_or(0.0));
+        println!("{} {}", overall_bar, Self::format_percentage(overall_percentage));
+        println!("{} {:.1}h/week", "Scheduled:".bold(), total_scheduled);
+
+        let tag_totals = Self::tag_totals(self.tagged_subjects(tag).map(|(_, subject)| subject));
+        if !tag_totals.is_empty() {
+            println!("\n{}", "By tag:".bold());
+            for (tag, completed, target) in tag_totals {
+                println!("  {}: {:.1}/{:.1} hours ({})", tag, completed, target, Self::format_percentage(Self::percentage(completed, target)));
+            }
+        }
+
+        let (current, longest) = self.streaks();
+        println!("\n{} current {}, longest {}", "Streak:".bold(), Self::format_days(current), Self::format_days(longest));
+    }
+
+    pub fn show_streak(&self) {
+        let (current, longest) = self.streaks();
+        println!("current streak: {}", Self::format_days(current).green().bold());
+        println!("longest streak: {}", Self::format_days(longest));
+    }
+
+    // (current, longest) in days
+    pub fn streaks(&self) -> (u32, u32) {
+        Self::count_streaks(&self.config.study_days, Local::now().date_naive())
+    }
+
+    // today not being studied yet doesn't break the streak, a whole missed day does
+    fn count_streaks(study_days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+
+        for day in study_days.iter().filter(|day| **day <= today) {
+            run = match previous {
+                Some(previous) if previous.succ_opt() == Some(*day) => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            previous = Some(*day);
+        }
+
+        let yesterday = today.pred_opt();
+        let current = match previous {
+            Some(last) if last == today || Some(last) == yesterday => run,
+            _ => 0,
+        };
+
+        (current, longest)
+    }
+
+    fn format_days(days: u32) -> String {
+        format!("{} day{}", days, if days == 1 { "" } else { "s" })
+    }
+
+    // the mean of every subject's own percentage, capped at 100 so one overachieving subject
+    // can't cover for the rest. subjects without a usable target are left out
+    fn balanced_percentage<'a>(subjects: impl Iterator<Item = &'a Subject>) -> Option<f32> {
+        let percentages: Vec<f32> = subjects
+            .filter_map(|subject| Self::percentage(subject.completed_hours, subject.target_hours))
+            .map(|percentage| percentage.min(100.0))
+            .collect();
+
+        if percentages.is_empty() {
+            return None;
+        }
+
+        Some(percentages.iter().sum::<f32>() / percentages.len() as f32)
+    }
+
+    // None when there is no target to measure against, dividing would give NaN or inf
+    pub fn percentage(completed: f32, target: f32) -> Option<f32> {
+        if target <= 0.0 || !target.is_finite() || !completed.is_finite() {
+            return None;
+        }
+
+        Some(completed / target * 100.0)
+    }
+
+    fn format_last_studied(last_studied: Option<DateTime<Local>>, now: DateTime<Local>) -> String {
+        let last_studied = match last_studied {
+            Some(last_studied) => last_studied,
+            None => return "never".to_string(),
+        };
+
+        let elapsed = now - last_studied;
+        let (amount, unit) = if elapsed.num_days() >= 1 {
+            (elapsed.num_days(), "day")
+        } else if elapsed.num_hours() >= 1 {
+            (elapsed.num_hours(), "hour")
+        } else if elapsed.num_minutes() >= 1 {
+            (elapsed.num_minutes(), "minute")
+        } else {
+            return "just now".to_string();
+        };
+
+        format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
+    }
+
+    fn format_overachievement(subject: &Subject) -> Option<String> {
+        let over = subject.completed_hours - subject.target_hours;
+
+        if subject.target_hours > 0.0 && over > 0.0 {
+            Some(format!("(+{:.1}h over target)", over))
+        } else {
+            None
+        }
+    }
+
+    pub fn format_percentage(percentage: Option<f32>) -> String {
+        match percentage {
+            Some(percentage) => format!("{:.1}%", percentage),
+            None => "no data".to_string(),
+        }
+    }
+
+    fn progress_bar(&self, percentage: f32) -> ProgressBar {
+        let width = 30;
+        // anything past 100% still draws a full bar, show_progress flags the surplus separately
+        let filled = ((percentage / 100.0 * width as f32).round() as usize).min(width);
+
+        let color = if self.config.progress_colors {
+            Self::progress_color(percentage)
+        } else {
+            Color::Green
+        };
+
+        let (fill, blank) = if self.ascii { ("#", "-") } else { ("█", "░") };
+
+        ProgressBar { filled, empty: width - filled, fill, blank, color }
+    }
+
+    fn generate_progress_bar(&self, percentage: f32) -> String {
+        let bar = self.progress_bar(percentage);
+        format!("[{}{}]", bar.fill.repeat(bar.filled).color(bar.color), bar.blank.repeat(bar.empty))
+    }
+
+    fn progress_color(percentage: f32) -> Color {
+        if percentage < 33.0 {
+            Color::Red
+        } else if percentage <= 66.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::error::Error;
+    use crate::config::{Config, Subject, StudySession};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Arc, atomic::AtomicBool};
+    use tempfile::{TempDir, tempdir};
+    use std::fs;
+    use mockall::{mock, predicate::*};
+
+    mock! {
+        pub Notifier {}
+
+        impl Notify for Notifier {
+            fn notify(&self, title: &str, message: &str, urgency: Option<Urgency>) -> Result<(), Box<dyn Error>>;
+        }
+    }
+
+    fn create_test_config() -> Config {
+        let temp_dir = tempdir().expect("unable to set up the temporary directory");
+        let config_path = temp_dir.path().join("test-config.json");
+
+        let mut subjects = HashMap::new();
+        subjects.insert("message queues".to_string(), Subject {
+            target_hours: 10.0,
+            completed_hours: 0.0,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        });
+
+        let mut schedules = HashMap::new();
+        schedules.insert("message queues".to_string(), vec![StudySession::new("Monday", "09:00", 60)]);
+
+        Config {subjects, schedules, config_path, ..Config::default()}
+    }
+
+    // the directory lives as long as the returned guard, keep it bound for the whole test
+    fn test_config_path() -> (TempDir, PathBuf) {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("test-config.json");
+        (temp_dir, config_path)
+    }
+
+    #[test]
+    fn test_scheduler_new() {
+        let config = create_test_config();
+        config.save().unwrap();
+
+        let result = Scheduler::new(&config.config_path);
+        assert!(result.is_ok());
+
+        let scheduler = result.unwrap();
+        assert!(scheduler.config.subjects.contains_key("message queues"));
+        assert_eq!(scheduler.config.schedules.get("message queues").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_scheduler_new_creates_missing_config() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("fresh").join("config.json");
+
+        let scheduler = Scheduler::new(&config_path).unwrap();
+        assert!(scheduler.config.subjects.is_empty());
+        assert!(config_path.exists());
+        assert_eq!(Config::load_from(&config_path).unwrap().reminder_lead_minutes, Config::default().reminder_lead_minutes);
+
+        // only a missing file is created, a corrupted one is left alone
+        std::fs::write(&config_path, "{ not json").unwrap();
+        assert!(matches!(Scheduler::new(&config_path), Err(StudyTimerError::ConfigCorrupted { .. })));
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), "{ not json");
+    }
+
+    #[test]
+    fn test_scheduler_init() {
+        let (_temp_dir, config_path) = test_config_path();
+        let result = Scheduler::init(&config_path);
+        assert!(result.is_ok());
+
+        let scheduler = result.unwrap();
+        assert!(scheduler.config.subjects.is_empty());
+        assert!(scheduler.config.schedules.is_empty());
+        assert!(!scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_add_subject() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+
+        let result = scheduler.add_subject("message queues", 500.0, 0.0, SubjectOptions::default());
+        assert!(result.is_ok());
+
+        assert!(scheduler.config.subjects.contains_key("message queues"));
+        assert_eq!(scheduler.config.subjects.get("message queues").unwrap().target_hours, 500.0);
+
+        let result = scheduler.add_subject("mq's", -2.0, 0.0, SubjectOptions::default());
+        assert!(matches!(result, Err(StudyTimerError::InvalidTarget(_))));
+
+        let result = scheduler.add_subject("mq's", 2.0, 0.0, SubjectOptions { weekly_target: Some(-1.0), ..Default::default() });
+        assert!(result.is_err());
+        assert!(!scheduler.config.subjects.contains_key("mq's"));
+
+        let result = scheduler.add_subject("mq's", 2.0, 0.0, SubjectOptions { tags: vec![" ".to_string()], ..Default::default() });
+        assert!(matches!(result, Err(StudyTimerError::InvalidTag(_))));
+        assert!(!scheduler.config.subjects.contains_key("mq's"));
+
+        scheduler.add_subject("kafka", 2.0, 0.0, SubjectOptions { tags: vec!["Backend".to_string()], ..Default::default() }).unwrap();
+        let saved = Config::load_from(&config_path).unwrap();
+        assert_eq!(saved.subjects.get("kafka").unwrap().tags, vec!["backend".to_string()]);
+    }
+
+    #[test]
+    fn test_add_schedule() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+
+        let result = scheduler.add_schedule("sys arch", "Tuesday", "14:00", 30, None);
+        assert!(result.is_ok());
+
+        let sessions = scheduler.config.schedules.get("sys arch").unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].day, "Tuesday");
+        assert_eq!(sessions[0].start_time, "14:00");
+        assert_eq!(sessions[0].duration, 30);
+
+        let result = scheduler.add_schedule("s.a", "Wednesday", "12:00", 30, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_add_schedule_with_lead() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("dsa", "Monday", "09:00", 60, None).unwrap();
+        scheduler.add_schedule("dsa", "Tuesday,Thursday", "09:00", 60, Some(30)).unwrap();
+
+        let leads: Vec<u32> = scheduler.config.schedules["dsa"].iter().map(|session| session.lead(5)).collect();
+        assert_eq!(leads, vec![5, 30, 30]);
+
+        // moving keeps the override
+        scheduler.move_schedule("dsa", "Tuesday", "09:00", "Wednesday", "10:00").unwrap();
+        assert!(scheduler.config.schedules["dsa"].iter().any(|session| session.day == "Wednesday" && session.lead_minutes == Some(30)));
+
+        let result = scheduler.add_schedule("dsa", "Friday", "09:00", 60, Some(24 * 60 + 1));
+        assert!(matches!(result, Err(StudyTimerError::InvalidSetting(_))));
+        assert_eq!(scheduler.config.schedules["dsa"].len(), 3);
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("math", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("physics", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("chem", 10.0, 0.0, SubjectOptions::default()).unwrap();
+
+        scheduler.add_schedule("math", "Monday", "09:00", 60, None).unwrap();
+        scheduler.add_schedule("chem", "Monday", "10:00", 60, None).unwrap();
+        assert!(scheduler.find_conflicts().is_empty());
+
+        // cross-subject clashes only warn, the session is still added
+        scheduler.add_schedule("physics", "Monday", "09:30", 60, None).unwrap();
+        assert_eq!(scheduler.config.schedules.get("physics").unwrap().len(), 1);
+
+        let conflicts = scheduler.find_conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!((conflicts[0].0).0, "chem");
+        assert_eq!((conflicts[0].1).0, "physics");
+        assert_eq!((conflicts[1].0).0, "math");
+        assert_eq!((conflicts[1].1).0, "physics");
+
+        let clashes = scheduler.conflicts_with("physics", &scheduler.config.schedules.get("physics").unwrap()[0]);
+        assert_eq!(clashes.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_subject() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("sys arch", "Monday", "08:00", 45, None).unwrap();
+
+        let result = scheduler.remove_subject("sys arch");
+        assert_eq!(result.unwrap(), 1);
+        assert!(!scheduler.config.subjects.contains_key("sys arch"));
+        assert!(!scheduler.config.schedules.contains_key("sys arch"));
+
+        let result = scheduler.remove_subject("sys arch");
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("config.json");
+        let mut scheduler = Scheduler::init(&config_path).unwrap().with_dry_run(true);
+
+        // validation still runs
+        assert!(matches!(scheduler.add_subject("dsa", -1.0, 0.0, SubjectOptions::default()), Err(StudyTimerError::InvalidTarget(_))));
+        assert!(!scheduler.has_unsaved_changes());
+
+        scheduler.add_subject("dsa", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("dsa", "Monday", "09:00", 60, None).unwrap();
+        assert!(scheduler.has_unsaved_changes());
+        assert_eq!(scheduler.config.schedules["dsa"].len(), 1);
+
+        assert!(Config::load_from(&config_path).unwrap().subjects.is_empty());
+    }
+
+    #[test]
+    fn test_unsaved() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("config.json");
+
+        let scheduler = Scheduler::unsaved(&config_path);
+        assert!(scheduler.config.subjects.is_empty());
+        assert_eq!(scheduler.config.config_path, config_path);
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    fn test_import() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let mut scheduler = Scheduler::init(&temp_dir.path().join("config.json")).unwrap();
+        scheduler.add_subject("DSA", 10.0, 2.0, SubjectOptions::default()).unwrap();
+
+        let path = temp_dir.path().join("subjects.csv");
+        fs::write(&path, concat!("dsa,40\n", "os,20\n", "networks,-3\n", "compilers,ten\n")).unwrap();
+
+        let summary = scheduler.import(&path, false).unwrap();
+        assert_eq!(summary.added, vec!["os".to_string()]);
+        assert_eq!(summary.skipped, vec!["DSA".to_string()]);
+        assert!(summary.updated.is_empty());
+        assert_eq!(summary.failed.iter().map(|(location, _)| location.as_str()).collect::<Vec<_>>(), vec!["line 3", "line 4"]);
+        assert_eq!(scheduler.config.subjects.get("DSA").unwrap().target_hours, 10.0);
+
+        let summary = scheduler.import(&path, true).unwrap();
+        assert_eq!(summary.updated, vec!["DSA".to_string(), "os".to_string()]);
+        assert!(summary.added.is_empty());
+        let dsa = scheduler.config.subjects.get("DSA").unwrap();
+        assert_eq!((dsa.target_hours, dsa.completed_hours), (40.0, 2.0));
+
+        let saved = Config::load_from(&temp_dir.path().join("config.json")).unwrap();
+        assert_eq!(saved.subjects.len(), 2);
+
+        let result = scheduler.import(&temp_dir.path().join("missing.csv"), false);
+        assert!(matches!(result, Err(StudyTimerError::Io(_))));
+    }
+
+    #[test]
+    fn test_log_hours() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+
+        let result = scheduler.log_hours("sys arch", 2.5);
+        assert!(result.is_ok());
+        assert_eq!(scheduler.config.subjects.get("sys arch").unwrap().completed_hours, 2.5);
+
+        let result = scheduler.log_hours("sys arch", -2.5);
+        assert!(matches!(result, Err(StudyTimerError::InvalidHours(_))));
+
+        let result = scheduler.log_hours("s.a", 1.0);
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+    }
+
+    #[test]
+    fn test_list_subjects() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("sys arch", "Monday", "08:00", 45, None).unwrap();
+
+        scheduler.list_subjects(None, &ListFilter::default()).unwrap();
+        scheduler.list_subjects(None, &ListFilter { subjects: vec!["Sys Arch".to_string()], days: vec!["Tuesday".to_string()] }).unwrap();
+    }
+
+    #[test]
+    fn test_list_reports_filtered() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("Math", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("dsa", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("Math", "Monday", "09:00", 60, None).unwrap();
+        scheduler.add_schedule("Math", "Tuesday", "09:00", 60, None).unwrap();
+        scheduler.add_schedule("os", "Monday", "14:00", 60, None).unwrap();
+
+        let filter = |subjects: &[&str], days: &[&str]| ListFilter {
+            subjects: subjects.iter().map(|s| s.to_string()).collect(),
+            days: days.iter().map(|s| s.to_string()).collect(),
+        };
+        let names = |reports: &[SubjectReport]| reports.iter().map(|report| report.name.to_string()).collect::<Vec<_>>();
+
+        // no filters, the same as subject_reports
+        let reports = scheduler.list_reports(None, &ListFilter::default()).unwrap();
+        assert_eq!(names(&reports), ["Math", "dsa", "os"]);
+        assert_eq!(reports[0].sessions.len(), 2);
+
+        let reports = scheduler.list_reports(None, &filter(&["math"], &["Monday"])).unwrap();
+        assert_eq!(names(&reports), ["Math"]);
+        assert_eq!(reports[0].sessions.len(), 1);
+        assert_eq!(reports[0].sessions[0].day, "Monday");
+
+        // an asked-for subject shows even with nothing on the day
+        let reports = scheduler.list_reports(None, &filter(&["Math", "dsa"], &["weekends"])).unwrap();
+        assert_eq!(names(&reports), ["Math", "dsa"]);
+        assert!(reports.iter().all(|report| report.sessions.is_empty()));
+
+        // with only a day, subjects with nothing on it are left out
+        let reports = scheduler.list_reports(None, &filter(&[], &["Monday"])).unwrap();
+        assert_eq!(names(&reports), ["Math", "os"]);
+        let reports = scheduler.list_reports(None, &filter(&[], &["Monday", "Tuesday"])).unwrap();
+        assert_eq!(reports[0].sessions.len(), 2);
+
+        assert!(matches!(scheduler.list_reports(None, &filter(&["Math", "physics"], &[])), Err(StudyTimerError::SubjectNotFound(_))));
+        assert!(matches!(scheduler.list_reports(None, &filter(&[], &["Someday"])), Err(StudyTimerError::InvalidDay(_))));
+    }
+
+    #[test]
+    fn test_stop_daemon() {
+        let (_temp_dir, config_path) = test_config_path();
+        let scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.running.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+
+        let result = scheduler.stop_daemon();
+        assert!(result.is_ok());
+
+        assert!(!scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_show_progress() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.config.subjects.insert("sys arch".to_string(), Subject {
+            target_hours: 100.0,
+            completed_hours: 20.0,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        });
+
+        scheduler.config.subjects.insert("dsa".to_string(), Subject {
+            target_hours: 20.0,
+            completed_hours: 15.0,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        });
+
+        scheduler.show_progress(false, None);
+    }
+
+    #[test]
+    fn test_progress_report_json() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.config.subjects.insert("sys arch".to_string(), Subject { target_hours: 100.0, completed_hours: 20.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() });
+        scheduler.config.subjects.insert("dsa".to_string(), Subject { target_hours: 20.0, completed_hours: 10.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() });
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Friday", "10:00"), session("Monday", "09:00")]);
+
+        let json = serde_json::to_value(scheduler.progress_report(None)).unwrap();
+        assert_eq!(json["subjects"][0]["name"], "dsa");
+        assert_eq!(json["subjects"][0]["percentage"], 50.0);
+        assert_eq!(json["subjects"][0]["sessions"][0]["day"], "Monday");
+        assert_eq!(json["subjects"][1]["name"], "sys arch");
+        assert_eq!(json["subjects"][1]["sessions"].as_array().unwrap().len(), 0);
+        assert_eq!(json["total_target_hours"], 120.0);
+        assert_eq!(json["overall_percentage"], 25.0);
+        assert_eq!(json["balanced_percentage"], 35.0);
+
+        let (_temp_dir, config_path) = test_config_path();
+        let empty = Scheduler::init(&config_path).unwrap();
+        let json = serde_json::to_value(empty.progress_report(None)).unwrap();
+        assert!(json["overall_percentage"].is_null());
+    }
+
+    #[test]
+    fn test_tag_filter_and_totals() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 20.0, 5.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 5.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("guitar", 50.0, 10.0, SubjectOptions::default()).unwrap();
+        scheduler.set_tags("dsa", &["university".to_string(), "cs".to_string()]).unwrap();
+        scheduler.set_tags("os", &["university".to_string()]).unwrap();
+        scheduler.set_tags("guitar", &["hobby".to_string()]).unwrap();
+
+        let names: Vec<&str> = scheduler.subject_reports(Some("University")).iter().map(|report| report.name).collect();
+        assert_eq!(names, vec!["dsa", "os"]);
+        assert_eq!(scheduler.subject_reports(None).len(), 3);
+        assert!(scheduler.subject_reports(Some("work")).is_empty());
+
+        let totals = Scheduler::tag_totals(scheduler.config.subjects.values());
+        assert_eq!(totals, vec![("cs", 5.0, 20.0), ("hobby", 10.0, 50.0), ("university", 10.0, 30.0)]);
+
+        let json = serde_json::to_value(scheduler.progress_report(Some("university"))).unwrap();
+        assert_eq!(json["total_target_hours"], 30.0);
+    }
+
+    #[test]
+    fn test_balanced_percentage() {
+        let subject = |target_hours, completed_hours| Subject { target_hours, completed_hours, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() };
+
+        // a small finished subject and a big barely started one
+        let subjects = [subject(2.0, 2.0), subject(200.0, 20.0)];
+        assert_eq!(Scheduler::balanced_percentage(subjects.iter()), Some(55.0));
+
+        // overachieving is capped, an unmeasurable subject is skipped
+        let subjects = [subject(2.0, 6.0), subject(10.0, 0.0), subject(0.0, 3.0)];
+        assert_eq!(Scheduler::balanced_percentage(subjects.iter()), Some(50.0));
+
+        assert_eq!(Scheduler::balanced_percentage([subject(0.0, 0.0)].iter()), None);
+        assert_eq!(Scheduler::balanced_percentage(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_show_progress_empty() {
+        let (_temp_dir, config_path) = test_config_path();
+        let scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.show_progress(false, None);
+        scheduler.show_progress(true, Some("hobby"));
+
+        assert_eq!(Scheduler::percentage(0.0, 0.0), None);
+        assert_eq!(Scheduler::percentage(5.0, 0.0), None);
+        assert_eq!(Scheduler::percentage(5.0, 20.0), Some(25.0));
+        assert_eq!(Scheduler::format_percentage(None), "no data");
+        assert_eq!(Scheduler::format_percentage(Some(25.0)), "25.0%");
+    }
+
+    #[test]
+    fn test_generate_progress_bar() {
+        let (_temp_dir, config_path) = test_config_path();
+        let scheduler = Scheduler::init(&config_path).unwrap();
 
-        Ok(Self {
-            config,
-            notifier,
-            running: Arc::new(AtomicBool::new(false)),
-        })
+        let bar_0 = scheduler.generate_progress_bar(0.0);
+        assert!(bar_0.contains("░"));
+        assert!(!bar_0.contains("█"));
+        
+        let bar_50 = scheduler.generate_progress_bar(50.0);
+        assert!(bar_50.contains("█") && bar_50.contains("░"));
+
+        let bar_100 = scheduler.generate_progress_bar(100.0);
+        assert!(bar_100.contains("█"));
+        assert!(!bar_100.contains("░"));
+
+        let bar_150 = scheduler.generate_progress_bar(150.0);
+        assert_eq!(bar_150, bar_100);
+        assert!(!bar_150.contains("░"));
     }
 
-    pub fn init() -> Result<Self, Box<dyn Error>> {
-        let config = Config::default();
-        config.save()?;
-        let notifier = Notifier::new();
+    #[test]
+    fn test_progress_bar_without_color() {
+        let (_temp_dir, config_path) = test_config_path();
+        let scheduler = Scheduler::init(&config_path).unwrap();
 
-        Ok(Self {
-            config,
-            notifier,
-            running: Arc::new(AtomicBool::new(false)),
-        })
+        colored::control::set_override(false);
+        let bar = scheduler.generate_progress_bar(50.0);
+        colored::control::unset_override();
+
+        assert!(!bar.contains('\x1b'));
+        assert_eq!(bar, format!("[{}{}]", "█".repeat(15), "░".repeat(15)));
     }
 
-    pub fn add_subject(&mut self, name: &str, target_hours: f32) -> Result<(), Box<dyn Error>> {
-        self.config.add_subject(name, target_hours)?;
-        self.config.save()?;
-        Ok(())
+    #[test]
+    fn test_ascii_progress_bar() {
+        let (_temp_dir, config_path) = test_config_path();
+        let scheduler = Scheduler::init(&config_path).unwrap().with_ascii(true);
+
+        colored::control::set_override(false);
+        let bars: Vec<String> = [0.0, 33.0, 50.0, 100.0, 150.0].iter().map(|percentage| scheduler.generate_progress_bar(*percentage)).collect();
+        colored::control::unset_override();
+
+        for bar in &bars {
+            assert!(bar.is_ascii(), "{} is not ascii", bar);
+            assert_eq!(bar.len(), 32);
+        }
+        assert_eq!(bars[2], format!("[{}{}]", "#".repeat(15), "-".repeat(15)));
     }
 
-    pub fn add_schedule(&mut self, subject: &str, day: &str, start_time: &str, duration: u32) -> Result<(), Box<dyn Error>> {
-        self.config.add_schedule(subject, day, start_time, duration)?;
-        self.config.save()?;
-        Ok(())
+    #[test]
+    fn test_progress_color() {
+        assert_eq!(Scheduler::progress_color(0.0), Color::Red);
+        assert_eq!(Scheduler::progress_color(32.9), Color::Red);
+        assert_eq!(Scheduler::progress_color(33.0), Color::Yellow);
+        assert_eq!(Scheduler::progress_color(66.0), Color::Yellow);
+        assert_eq!(Scheduler::progress_color(66.1), Color::Green);
+        assert_eq!(Scheduler::progress_color(150.0), Color::Green);
     }
 
-    pub fn list_subjects(&self) {
-        println!("{}", "Subjects and schedules:".bold());
-        println!("{}", "-".repeat(50));
+    #[test]
+    fn test_count_streaks() {
+        let date = |day: &str| NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+        let today = date("2024-01-17");
 
-        for (name, subject) in &self.config.subjects {
-            println!("{}: {} hours target", name.green().bold(), subject.target_hours);
-            println!(" Progress: {:.1}/{:.1} hours ({:.1}%)",
-            subject.completed_hours,
-            subject.target_hours,
-            (subject.completed_hours / subject.target_hours) * 100.0);
+        assert_eq!(Scheduler::count_streaks(&BTreeSet::new(), today), (0, 0));
 
-            if let Some(sessions) = self.config.schedules.get(name) {
-                println!(" Scheduled sessions:");
-                for session in sessions {
-                    println!("  {} at {} for {} minutes",
-                             session.day.blue(),
-                             session.start_time,
-                             session.duration);
-                }
-            } else {
-                println!("  No scheduled sessions");
-            }
-            println!();
-        }
+        let days: BTreeSet<NaiveDate> = ["2024-01-01", "2024-01-02", "2024-01-03", "2024-01-04", "2024-01-15", "2024-01-16", "2024-01-17"]
+            .iter()
+            .map(|day| date(day))
+            .collect();
+        assert_eq!(Scheduler::count_streaks(&days, today), (3, 4));
+
+        // not having studied yet today keeps yesterday's streak alive
+        assert_eq!(Scheduler::count_streaks(&days, date("2024-01-18")), (3, 4));
+
+        // a full missed day resets it
+        assert_eq!(Scheduler::count_streaks(&days, date("2024-01-19")), (0, 4));
+
+        // a run crossing a month boundary still counts as consecutive
+        let days: BTreeSet<NaiveDate> = ["2024-01-30", "2024-01-31", "2024-02-01"].iter().map(|day| date(day)).collect();
+        assert_eq!(Scheduler::count_streaks(&days, date("2024-02-01")), (3, 3));
     }
 
-    pub async fn run_daemon(&mut self) -> Result<(), Box<dyn Error>> {
-        self.running.store(true, Ordering::SeqCst);
-        let running = Arc::clone(&self.running);
+    #[test]
+    fn test_neglected_subjects() {
+        let now = at("2024-01-17", "12:00").and_local_timezone(Local).unwrap();
+        let studied = |target_hours: f32, completed_hours: f32, days_ago: Option<i64>| Subject {
+            target_hours,
+            completed_hours,
+            last_studied: days_ago.map(|days| now - chrono::Duration::days(days)),
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        };
 
-        let schedules = self.config.schedules.clone();
-        let subjects = self.config.subjects.clone();
-        let config_path = self.config.config_path.clone();
+        let mut subjects = HashMap::new();
+        subjects.insert("dsa".to_string(), studied(10.0, 2.0, Some(6)));
+        subjects.insert("os".to_string(), studied(10.0, 2.0, Some(5)));
+        subjects.insert("compilers".to_string(), studied(10.0, 10.0, Some(30)));
+        subjects.insert("networks".to_string(), studied(10.0, 0.0, None));
+        subjects.insert("algebra".to_string(), studied(10.0, 1.0, Some(9)));
+
+        assert_eq!(Scheduler::neglected_subjects(&subjects, 5, now), vec![("algebra", 9), ("dsa", 6)]);
+        assert_eq!(Scheduler::neglected_subjects(&subjects, 8, now), vec![("algebra", 9)]);
+        assert!(Scheduler::neglected_subjects(&subjects, 0, now).is_empty());
+    }
 
-        let notifier = self.notifier.clone();
+    #[test]
+    fn test_deadline_urgency() {
+        let mut config = create_test_config();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(Scheduler::deadline_urgency(&config, "message queues", today), None);
+        assert_eq!(Scheduler::deadline_urgency(&config, "compilers", today), None);
 
-        task::spawn(async move {
-            println!("study timer daemon started");
+        config.set_deadline("message queues", NaiveDate::from_ymd_opt(2024, 1, 25)).unwrap();
+        assert_eq!(Scheduler::deadline_urgency(&config, "message queues", today), None);
 
-            while running.load(Ordering::SeqCst) {
-                let now = Local::now();
-                let current_day = match now.weekday() {
-                    Weekday::Mon => "Monday",
-                    Weekday::Tue => "Tuesday",
-                    Weekday::Wed => "Wednesday",
-                    Weekday::Thu => "Thursday",
-                    Weekday::Fri => "Friday",
-                    Weekday::Sat => "Saturday",
-                    Weekday::Sun => "Sunday",
-                };
+        config.set_deadline("message queues", NaiveDate::from_ymd_opt(2024, 1, 18)).unwrap();
+        assert_eq!(Scheduler::deadline_urgency(&config, "message queues", today), Some(Urgency::Critical));
 
-                let current_time = now.format("%H:%M").to_string();
+        config.set_deadline("message queues", NaiveDate::from_ymd_opt(2024, 1, 10)).unwrap();
+        assert_eq!(Scheduler::deadline_urgency(&config, "message queues", today), Some(Urgency::Critical));
+    }
 
-                for (subject_name, sessions) in &schedules {
-                    for session in sessions {
-                        if session.day == current_day && session.start_time == current_time {
-                            let message = format!("Time to study {} for {} minutes", subject_name, session.duration);
-                            notifier.notify("Study Timer", &message);
-                        }
-                        if session.day == current_day {
-                            if let Ok(session_time) = NaiveTime::parse_from_str(&session.start_time, "%H:%M") {
-                                let now_time = NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap();
-                                let diff_minutes = (session_time.signed_duration_since(now_time).num_minutes() + 60) %60;
-
-                                if diff_minutes == 5 {
-                                    let message = format!("{} study session starts in 5 minutes", subject_name);
-                                    notifier.notify("study timer", &message);
-                                }
-                            }
-                        }
-                    }
-                }
+    #[test]
+    fn test_first_notification() {
+        let now = at("2024-01-15", "09:00").and_local_timezone(Local).unwrap();
+        let session = StudySession::new("Monday", "09:00", 60);
+        let mut recent = HashMap::new();
 
-                time::sleep(Duration::from_secs(60)).await;
-            }
+        assert!(Scheduler::first_notification(&mut recent, Scheduler::notification_key("start", "dsa", &session), now));
+        assert!(!Scheduler::first_notification(&mut recent, Scheduler::notification_key("start", "dsa", &session), now + chrono::Duration::seconds(50)));
+        assert!(Scheduler::first_notification(&mut recent, Scheduler::notification_key("pre-reminder", "dsa", &session), now));
+        assert!(Scheduler::first_notification(&mut recent, Scheduler::notification_key("start", "os", &session), now));
 
-            println!("study timer daemon stopped");
-        });
+        assert!(Scheduler::first_notification(&mut recent, Scheduler::notification_key("start", "dsa", &session), now + chrono::Duration::minutes(2)));
+    }
 
-        Ok(())
+    #[test]
+    fn test_format_deadline() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let subject = |completed_hours: f32, deadline: &str| Subject {
+            target_hours: 20.0,
+            completed_hours,
+            last_studied: None,
+            deadline: NaiveDate::parse_from_str(deadline, "%Y-%m-%d").ok(),
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        };
+
+        assert!(Scheduler::format_deadline(&subject(0.0, ""), today).is_none());
+        assert_eq!(Scheduler::format_deadline(&subject(10.0, "2024-01-21"), today).unwrap(), "2024-01-21, 4 days left, 2.0h/day needed");
+        assert_eq!(Scheduler::format_deadline(&subject(19.0, "2024-01-17"), today).unwrap(), "2024-01-17, 0 days left, 1.0h/day needed");
+        assert_eq!(Scheduler::format_deadline(&subject(20.0, "2024-01-10"), today).unwrap(), "2024-01-10, target reached");
+
+        colored::control::set_override(false);
+        let overdue = Scheduler::format_deadline(&subject(5.0, "2024-01-16"), today).unwrap();
+        colored::control::unset_override();
+        assert_eq!(overdue, "2024-01-16, 1 day overdue");
     }
 
-    pub fn stop_daemon(&self) -> Result<(), Box<dyn Error>> {
-        self.running.store(false, Ordering::SeqCst);
-        println!("sent stop signal to daemon");
-        Ok(())
+    #[test]
+    fn test_weekly_scheduled_hours() {
+        let sessions = vec![
+            StudySession::new("Monday", "09:00", 60),
+            StudySession::new("Thursday", "14:00", 90),
+            StudySession::once(NaiveDate::from_ymd_opt(2024, 1, 20).unwrap(), "10:00", 120),
+        ];
+
+        assert_eq!(Scheduler::weekly_scheduled_hours(&sessions), 2.5);
+        assert_eq!(Scheduler::weekly_scheduled_hours(&[]), 0.0);
     }
 
-    pub fn show_progress(&self) {
-        println!("{}", "study progress:".bold());
-        println!("{}", "-".repeat(50));
+    #[test]
+    fn test_deadline_shortfall() {
+        // a wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let subject = |completed_hours: f32, deadline: Option<NaiveDate>| Subject {
+            target_hours: 20.0,
+            completed_hours,
+            last_studied: None,
+            deadline,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        };
+        let sessions = vec![
+            StudySession::new("Monday", "09:00", 120),
+            StudySession::new("Thursday", "14:00", 60),
+            StudySession::once(NaiveDate::from_ymd_opt(2024, 1, 20).unwrap(), "10:00", 180),
+        ];
+
+        // thursday 18th, saturday 20th and monday 22nd
+        let deadline = NaiveDate::from_ymd_opt(2024, 1, 22);
+        assert_eq!(Scheduler::deadline_shortfall(&subject(10.0, deadline), &sessions, today), Some(4.0));
+        assert_eq!(Scheduler::deadline_shortfall(&subject(14.0, deadline), &sessions, today), None);
+        assert_eq!(Scheduler::deadline_shortfall(&subject(0.0, None), &sessions, today), None);
+        assert_eq!(Scheduler::deadline_shortfall(&subject(0.0, NaiveDate::from_ymd_opt(2024, 1, 10)), &sessions, today), None);
+        assert_eq!(Scheduler::deadline_shortfall(&subject(19.0, deadline), &[], today), Some(1.0));
+
+        assert_eq!(Scheduler::shortfall_warning(&subject(10.0, deadline), &sessions, today).unwrap(),
+                   "scheduled sessions leave 4.0h of the target undone by 2024-01-22, about 4.0h/week more is needed");
+        let far = NaiveDate::from_ymd_opt(2024, 2, 13);
+        assert_eq!(Scheduler::shortfall_warning(&subject(0.0, far), &[], today).unwrap(),
+                   "scheduled sessions leave 20.0h of the target undone by 2024-02-13, about 5.0h/week more is needed");
+        assert!(Scheduler::shortfall_warning(&subject(14.0, deadline), &sessions, today).is_none());
+    }
 
-        let mut total_completed = 0.0;
-        let mut total_target = 0.0;
+    #[test]
+    fn test_subjects_by_deadline() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("networks", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("algebra", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("dsa", 10.0, 0.0, SubjectOptions { deadline: NaiveDate::from_ymd_opt(2024, 3, 1), ..Default::default() }).unwrap();
+        scheduler.add_subject("os", 10.0, 0.0, SubjectOptions { deadline: NaiveDate::from_ymd_opt(2024, 2, 1), ..Default::default() }).unwrap();
+
+        let order: Vec<&str> = scheduler.subjects_by_deadline(None).iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["os", "dsa", "algebra", "networks"]);
+    }
 
-        for (name, subject) in &self.config.subjects {
-            total_completed += subject.completed_hours;
-            total_target += subject.target_hours;
+    #[test]
+    fn test_format_last_studied() {
+        let now = at("2024-01-17", "12:00").and_local_timezone(Local).unwrap();
+
+        assert_eq!(Scheduler::format_last_studied(None, now), "never");
+        assert_eq!(Scheduler::format_last_studied(Some(now), now), "just now");
+        assert_eq!(Scheduler::format_last_studied(Some(now - chrono::Duration::minutes(1)), now), "1 minute ago");
+        assert_eq!(Scheduler::format_last_studied(Some(now - chrono::Duration::minutes(90)), now), "1 hour ago");
+        assert_eq!(Scheduler::format_last_studied(Some(now - chrono::Duration::days(3)), now), "3 days ago");
+    }
 
-            let percentage = (subject.completed_hours / subject.target_hours) * 100.0;
-            let progress_bar = self.generate_progress_bar(percentage);
+    #[test]
+    fn test_format_overachievement() {
+        let over = Subject { target_hours: 10.0, completed_hours: 15.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() };
+        assert_eq!(Scheduler::format_overachievement(&over).unwrap(), "(+5.0h over target)");
 
-            println!("{}: {:.1}/{:.1} hours", name.green().bold(), subject.completed_hours, subject.target_hours);
-            println!("{} {:.1}%", progress_bar, percentage);
-        }
+        let exact = Subject { target_hours: 10.0, completed_hours: 10.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() };
+        assert!(Scheduler::format_overachievement(&exact).is_none());
 
-        println!("\n{}", "Overall progress:".bold());
-        let overall_percentage = (total_completed / total_target) * 100.0;
-        let overall_bar = self.generate_progress_bar(overall_percentage);
-        println!("{} {:.1}%", overall_bar, overall_percentage);
+        let under = Subject { target_hours: 10.0, completed_hours: 2.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() };
+        assert!(Scheduler::format_overachievement(&under).is_none());
     }
 
-    fn generate_progress_bar(&self, percentage: f32) -> String {
-        let width = 30;
-        let filled = (percentage / 100.0 * width as f32).round() as usize;
-        let empty = width - filled;
+    fn session(day: &str, start_time: &str) -> StudySession {
+        StudySession::new(day, start_time, 60)
+    }
 
-        format!("[{}{}]", "█".repeat(filled).green(), "░".repeat(empty))
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
     }
-}
 
+    // drops colored's escape codes, turning colors off instead would race the other tests
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::{Config, Subject, StudySession};
-    use std::collections::HashMap;
-    use std::path::PathBuf;
-    use std::sync::{Arc, atomic::AtomicBool};
-    use tempfile::tempdir;
-    use std::fs;
-    use mockall::{mock, predicate::*};
+        plain
+    }
 
-    mock! {
-        pub Notifier {
-            pub fn new() -> Self;
-            pub fn notify(&self, title: &str, message: &str) -> Result<(), Box<dyn Error>>;
-            pub fn clone(&self) -> MockNotifier;
-        }
+    #[test]
+    fn test_minutes_until_session_across_hour() {
+        // 2024-01-15 is a Monday
+        let session = session("Monday", "10:02");
+
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-15", "09:57"), &session), Some(5));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-15", "09:58"), &session), Some(4));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-15", "10:02"), &session), Some(0));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-15", "08:57"), &session), Some(65));
     }
 
-    fn create_test_config() -> Config {
-        let temp_dir = tempdir().expect("unable to set up the temporary directory");
-        let config_path = temp_dir.path().join("test-config.json");
+    #[test]
+    fn test_minutes_until_session_across_midnight() {
+        // 2024-01-14 is a Sunday
+        let session = session("Monday", "00:02");
 
-        let mut subjects = HashMap::new();
-        subjects.insert("message queues".to_string(), Subject {
-            target_hours: 10.0,
-            completed_hours: 0.0,
-        });
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-14", "23:57"), &session), Some(5));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-14", "23:58"), &session), Some(4));
+    }
 
-        let mut schedules = HashMap::new();
-        let mut mq_sessions = Vec::new();
-        mq_sessions.push(StudySession {
-            day: "Monday".to_string(),
-            start_time: "09:00".to_string(),
-            duration: 60,
-        });
-        schedules.insert("message queues".to_string(), mq_sessions);
+    #[test]
+    fn test_minutes_until_session_other_day() {
+        let session = session("Tuesday", "10:02");
 
-        Config {subjects, schedules, config_path}
+        let minutes = Scheduler::minutes_until_session(&at("2024-01-15", "09:57"), &session);
+        assert_eq!(minutes, Some(24 * 60 + 5));
+
+        // already passed this week, next occurrence is a week out
+        let minutes = Scheduler::minutes_until_session(&at("2024-01-16", "10:03"), &session);
+        assert_eq!(minutes, Some(7 * 24 * 60 - 1));
     }
 
-    //TODO:fix this test, as of now i've implemented a simple scheduler_init check but that misses
-    //the point as it's proper implementation will require code adjustments which is overkill.
     #[test]
-    fn test_scheduler_new() {
-        let config = create_test_config();
-        config.save().unwrap();
+    fn test_minutes_until_one_time_session() {
+        let session = StudySession::once(NaiveDate::from_ymd_opt(2024, 1, 22).unwrap(), "10:00", 60);
 
-        let result = Scheduler::new();
-        assert!(result.is_ok());
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-15", "10:00"), &session), Some(7 * 24 * 60));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-22", "09:55"), &session), Some(5));
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-22", "10:01"), &session), None);
+        assert_eq!(Scheduler::minutes_until_session(&at("2024-01-29", "09:55"), &session), None);
     }
 
     #[test]
-    fn test_scheduler_init() {
-        let result = Scheduler::init();
-        assert!(result.is_ok());
+    fn test_range_hours() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 10.0, 5.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 3.0, SubjectOptions::default()).unwrap();
+
+        let start = at("2024-01-15", "09:00").and_local_timezone(Local).unwrap();
+        for (subject, days, minutes) in [("dsa", 0, 90.0), ("dsa", 20, 60.0), ("networks", 1, 30.0)] {
+            let start = start + chrono::Duration::days(days);
+            scheduler.history.append(&HistoryEntry::new(subject, start, start + chrono::Duration::minutes(minutes as i64), minutes)).unwrap();
+        }
 
-        let scheduler = result.unwrap();
-        assert!(scheduler.config.subjects.is_empty());
-        assert!(scheduler.config.schedules.is_empty());
-        assert!(!scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1);
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31);
+        let hours = scheduler.range_hours(from, to, None).unwrap();
+        assert_eq!(hours, BTreeMap::from([("dsa".to_string(), 1.5), ("networks".to_string(), 0.5), ("os".to_string(), 0.0)]));
+
+        let hours = scheduler.range_hours(None, None, None).unwrap();
+        assert_eq!(hours["dsa"], 2.5);
+
+        assert!(matches!(scheduler.range_hours(to, from, None), Err(StudyTimerError::InvalidDate(_))));
     }
 
     #[test]
-    fn test_add_subject() {
-        let mut scheduler = Scheduler::init().unwrap();
+    fn test_dashboard() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let mut scheduler = Scheduler::init(&temp_dir.path().join("config.json"))
+            .unwrap()
+            .with_pid_file(PidFile::from_path(temp_dir.path().join("test.pid")))
+            .with_ascii(true);
+        scheduler.add_subject("os", 10.0, 2.5, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("dsa", 10.0, 5.0, SubjectOptions::default()).unwrap();
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "08:00"), session("Monday", "10:00")]);
+
+        let dashboard = scheduler.dashboard(&at("2024-01-15", "09:30")).unwrap();
+        assert!(matches!(dashboard.daemon, DaemonState::Stopped));
+        assert_eq!(dashboard.subjects.iter().map(|subject| subject.name.as_str()).collect::<Vec<_>>(), vec!["dsa", "os"]);
+        assert_eq!((dashboard.subjects[0].bar.filled, dashboard.subjects[0].bar.fill), (15, "#"));
+        assert_eq!(dashboard.today.iter().map(|(_, session, ended)| (session.start_time.as_str(), *ended)).collect::<Vec<_>>(),
+                   vec![("08:00", true), ("10:00", false)]);
+
+        let (subject, session, seconds) = dashboard.next.unwrap();
+        assert_eq!((subject.as_str(), session.start_time.as_str(), seconds), ("dsa", "10:00", 30 * 60));
+    }
 
-        let result = scheduler.add_subject("message queues", 500.0);
-        assert!(result.is_ok());
+    #[test]
+    fn test_digest_held_through_dnd() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let mut held = None;
+
+        // due inside the window, it goes out on the first pass after the window
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, Some(()), today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today, false), Some(()));
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today, false), None);
+
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, Some(()), today, false), Some(()));
+
+        // a window running past midnight, the digest is stale by the time it ends
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, Some(()), today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today.succ_opt().unwrap(), false), None);
+        assert!(held.is_none());
+    }
 
-        assert!(scheduler.config.subjects.contains_key("message queues"));
-        assert_eq!(scheduler.config.subjects.get("message queues").unwrap().target_hours, 500.0);
+    #[test]
+    fn test_catch_up_held_through_dnd() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let missed = Scheduler::missed_message(&[("dsa", &session("Monday", "07:00"))]);
+        let mut held = None;
+
+        // woke up inside the window, the catch-up waits for it to end
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, Some(missed.clone()), today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today, false), Some(missed.clone()));
+        assert!(held.is_none());
+
+        // missed sessions are today's, so a catch-up held into tomorrow is dropped
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, Some(missed), today, true), None);
+        assert_eq!(Scheduler::hold_for_dnd(&mut held, None, today.succ_opt().unwrap(), false), None);
+        assert!(held.is_none());
+    }
 
-        let result = scheduler.add_subject("mq's", -2.0);
-        assert!(result.is_err());
+    #[test]
+    fn test_session_ends_within() {
+        // a single regular tick, the minute before up to the given one
+        let ends_at = |session: &StudySession, now: NaiveDateTime| {
+            Scheduler::session_ends_within(session, &(now - chrono::Duration::minutes(1)), &now).is_some()
+        };
+
+        let monday = session("Monday", "09:00");
+        assert!(ends_at(&monday, at("2024-01-15", "10:00")));
+        assert!(!ends_at(&monday, at("2024-01-15", "09:59")));
+        assert!(!ends_at(&monday, at("2024-01-16", "10:00")));
+
+        // ends after midnight, on the tuesday
+        let late = StudySession::new("Monday", "23:30", 60);
+        assert!(ends_at(&late, at("2024-01-16", "00:30")));
+        assert!(!ends_at(&late, at("2024-01-15", "00:30")));
+
+        let once = StudySession::once(NaiveDate::from_ymd_opt(2024, 1, 22).unwrap(), "09:00", 30);
+        assert!(ends_at(&once, at("2024-01-22", "09:30")));
+        assert!(!ends_at(&once, at("2024-01-15", "09:30")));
     }
 
     #[test]
-    fn test_add_schedule() {
-        let mut scheduler = Scheduler::init().unwrap();
-        scheduler.add_subject("sys arch", 100.0).unwrap();
+    fn test_missed_sessions() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "09:00"), session("Sunday", "23:00")]);
+        scheduler.config.schedules.insert("os".to_string(), vec![session("Monday", "07:30"), session("Monday", "12:00")]);
+
+        // asleep from sunday 22:00 until monday 11:00, yesterday's session isn't brought up
+        let missed = Scheduler::missed_sessions(&scheduler.config, &at("2024-01-14", "22:00"), &at("2024-01-15", "11:00"));
+        assert_eq!(missed.len(), 2);
+        assert_eq!((missed[0].0, missed[0].1.start_time.as_str()), ("os", "07:30"));
+        assert_eq!((missed[1].0, missed[1].1.start_time.as_str()), ("dsa", "09:00"));
+        assert_eq!(Scheduler::missed_message(&missed), "you missed os at 07:30, dsa at 09:00");
+
+        // a session that started in the last minute before waking was already announced by the last pass
+        assert!(Scheduler::missed_sessions(&scheduler.config, &at("2024-01-15", "09:00"), &at("2024-01-15", "11:00")).is_empty());
+    }
 
-        let result = scheduler.add_schedule("sys arch", "Tuesday", "14:00", 30);
-        assert!(result.is_ok());
+    #[test]
+    fn test_tick_range_across_dst() {
+        let sunday = session("Sunday", "02:30");
+        let early = session("Sunday", "01:30");
+
+        // spring forward, 02:00-02:59 never happens so 02:30 is announced at 03:00
+        let (from, to) = Scheduler::tick_range(Some(at("2024-03-10", "01:58")), at("2024-03-10", "01:59"));
+        assert!(!Scheduler::session_starts_within(&sunday, &from, &to));
+        let (from, to) = Scheduler::tick_range(Some(to), at("2024-03-10", "03:00"));
+        assert_eq!((from, to), (at("2024-03-10", "01:59"), at("2024-03-10", "03:00")));
+        assert!(Scheduler::session_starts_within(&sunday, &from, &to));
+        let (from, to) = Scheduler::tick_range(Some(to), at("2024-03-10", "03:01"));
+        assert!(!Scheduler::session_starts_within(&sunday, &from, &to));
+
+        // fall back, 01:00-01:59 comes round twice but 01:30 is only announced the first time
+        let (from, to) = Scheduler::tick_range(Some(at("2024-11-03", "01:29")), at("2024-11-03", "01:30"));
+        assert!(Scheduler::session_starts_within(&early, &from, &to));
+        let (from, to) = Scheduler::tick_range(Some(at("2024-11-03", "01:59")), at("2024-11-03", "01:00"));
+        assert_eq!(from, to);
+        let (from, to) = Scheduler::tick_range(Some(to), at("2024-11-03", "01:30"));
+        assert!(!Scheduler::session_starts_within(&early, &from, &to));
+        let (from, to) = Scheduler::tick_range(Some(to), at("2024-11-03", "02:00"));
+        assert_eq!((from, to), (at("2024-11-03", "01:59"), at("2024-11-03", "02:00")));
+
+        // the first pass, and a jump too big for a dst shift, only cover the current minute
+        assert_eq!(Scheduler::tick_range(None, at("2024-03-10", "03:00")).0, at("2024-03-10", "02:59"));
+        assert_eq!(Scheduler::tick_range(Some(at("2024-03-10", "09:00")), at("2024-03-10", "15:00")).0, at("2024-03-10", "14:59"));
+    }
 
-        let sessions = scheduler.config.schedules.get("sys arch").unwrap();
-        assert_eq!(sessions.len(), 1);
-        assert_eq!(sessions[0].day, "Tuesday");
-        assert_eq!(sessions[0].start_time, "14:00");
-        assert_eq!(sessions[0].duration, 30);
+    #[test]
+    fn test_summary_line() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        assert_eq!(strip_ansi(&scheduler.summary_line(&at("2024-01-15", "09:00"))), "0 subjects · no progress yet · nothing scheduled");
+
+        scheduler.add_subject("dsa", 10.0, 5.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 3.4, SubjectOptions::default()).unwrap();
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "10:12")]);
+        assert_eq!(strip_ansi(&scheduler.summary_line(&at("2024-01-15", "09:00"))), "2 subjects · 42% overall · next: dsa in 1h12m");
+        assert_eq!(strip_ansi(&scheduler.summary_line(&at("2024-01-15", "10:12"))), "2 subjects · 42% overall · next: dsa now");
+
+        let scheduler = scheduler.with_ascii(true);
+        assert_eq!(strip_ansi(&scheduler.summary_line(&at("2024-01-15", "09:00"))), "2 subjects | 42% overall | next: dsa in 1h12m");
+    }
 
-        let result = scheduler.add_schedule("s.a", "Wednesday", "12:00", 30);
-        assert!(result.is_err());
+    #[test]
+    fn test_next_session() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        assert!(scheduler.next_session(&at("2024-01-15", "09:00")).is_none());
+
+        scheduler.config.subjects.insert("dsa".to_string(), Subject { target_hours: 10.0, completed_hours: 0.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() });
+        scheduler.config.subjects.insert("os".to_string(), Subject { target_hours: 10.0, completed_hours: 0.0, last_studied: None, deadline: None, weekly_target: None, weekly_completed: 0.0, tags: Vec::new() });
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "08:00"), session("Wednesday", "10:00")]);
+        scheduler.config.schedules.insert("os".to_string(), vec![session("Tuesday", "09:00")]);
+
+        let (subject, next, minutes) = scheduler.next_session(&at("2024-01-15", "09:00")).unwrap();
+        assert_eq!(subject, "os");
+        assert_eq!(next.day, "Tuesday");
+        assert_eq!(minutes, 24 * 60);
+
+        // after the last session of the week, wrap around to next monday
+        let (subject, next, minutes) = scheduler.next_session(&at("2024-01-17", "11:00")).unwrap();
+        assert_eq!(subject, "dsa");
+        assert_eq!(next.day, "Monday");
+        assert_eq!(minutes, 4 * 24 * 60 + 21 * 60);
     }
 
     #[test]
-    fn test_list_subjects() {
-        let mut scheduler = Scheduler::init().unwrap();
-        scheduler.add_subject("sys arch", 100.0).unwrap();
-        scheduler.add_schedule("sys arch", "Monday", "08:00", 45).unwrap();
+    fn test_sessions_on() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "14:00"), session("Tuesday", "08:00")]);
+        scheduler.config.schedules.insert("os".to_string(), vec![session("Monday", "09:00")]);
+
+        // 2024-01-15 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        scheduler.config.schedules.get_mut("os").unwrap().push(StudySession::once(monday, "11:00", 60));
+
+        let sessions = scheduler.sessions_on(monday);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].0, "os");
+        assert_eq!(sessions[0].1.start_time, "09:00");
+        assert_eq!(sessions[1].1.start_time, "11:00");
+        assert_eq!(sessions[2].0, "dsa");
+        assert_eq!(sessions[2].1.start_time, "14:00");
+
+        // the one-off session doesn't come back the following monday
+        assert_eq!(scheduler.sessions_on(monday + chrono::Duration::days(7)).len(), 2);
+        assert!(scheduler.sessions_on(NaiveDate::from_ymd_opt(2024, 1, 21).unwrap()).is_empty());
+    }
 
-        scheduler.list_subjects();
+    #[test]
+    fn test_digest_message() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.config.schedules.insert("dsa".to_string(), vec![session("Monday", "14:00"), session("Tuesday", "08:00")]);
+        scheduler.config.schedules.insert("os".to_string(), vec![session("Monday", "09:00")]);
+
+        // 2024-01-15 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(Scheduler::digest_message(&scheduler.config, monday).unwrap(), "2 sessions today\n09:00-10:00 os\n14:00-15:00 dsa");
+        assert_eq!(Scheduler::digest_message(&scheduler.config, monday + chrono::Duration::days(1)).unwrap(), "1 session today\n08:00-09:00 dsa");
+
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 21).unwrap();
+        assert!(Scheduler::digest_message(&scheduler.config, sunday).is_none());
+
+        scheduler.config.digest_empty_days = true;
+        assert_eq!(Scheduler::digest_message(&scheduler.config, sunday).unwrap(), "nothing scheduled today");
     }
 
     #[test]
-    fn test_stop_daemon() {
-        let scheduler = Scheduler::init().unwrap();
-        scheduler.running.store(true, std::sync::atomic::Ordering::SeqCst);
-        assert!(scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+    fn test_sorted_sessions() {
+        let sessions = vec![session("Wednesday", "08:00"), session("Monday", "14:00"), session("Monday", "09:00")];
 
-        let result = scheduler.stop_daemon();
-        assert!(result.is_ok());
+        let sorted = Scheduler::sorted_sessions(&sessions);
+        let order: Vec<(&str, &str)> = sorted.iter().map(|s| (s.day.as_str(), s.start_time.as_str())).collect();
+        assert_eq!(order, vec![("Monday", "09:00"), ("Monday", "14:00"), ("Wednesday", "08:00")]);
+    }
 
-        assert!(!scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
+    #[test]
+    fn test_suggest_sessions() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 30.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("done", 5.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.log_hours("done", 5.0).unwrap();
+        scheduler.add_schedule("os", "Monday", "09:00", 60, None).unwrap();
+
+        let proposed = scheduler.suggest_sessions(8.0, 60).unwrap();
+
+        // 8h split 3:1 is 6h of dsa and 2h of os, os already has one hour scheduled
+        let count = |subject: &str| proposed.iter().filter(|(name, _)| name == subject).count();
+        assert_eq!(count("dsa"), 6);
+        assert_eq!(count("os"), 1);
+        assert_eq!(count("done"), 0);
+
+        // nothing overlaps the existing session or another suggestion
+        let existing = StudySession::new("Monday", "09:00", 60);
+        for (i, (_, session)) in proposed.iter().enumerate() {
+            assert!(!session.overlaps(&existing));
+            assert!(proposed[i + 1..].iter().all(|(_, other)| !other.overlaps(session)));
+            assert!(!["Saturday", "Sunday"].contains(&session.day.as_str()));
+        }
+
+        // dsa gets spread over the week before doubling up on a day
+        let dsa_days: HashSet<&str> = proposed.iter().filter(|(name, _)| name == "dsa").map(|(_, session)| session.day.as_str()).collect();
+        assert_eq!(dsa_days.len(), 5);
+
+        for (subject, session) in &proposed {
+            scheduler.add_schedule(subject, &session.day, &session.start_time, session.duration, None).unwrap();
+        }
+        assert!(scheduler.find_conflicts().is_empty());
+
+        assert!(matches!(scheduler.suggest_sessions(0.0, 60), Err(StudyTimerError::InvalidHours(_))));
+        assert!(matches!(scheduler.suggest_sessions(8.0, 0), Err(StudyTimerError::InvalidDuration(_))));
     }
 
     #[test]
-    fn test_show_progress() {
-        let mut scheduler = Scheduler::init().unwrap();
-        scheduler.config.subjects.insert("sys arch".to_string(), Subject {
-            target_hours: 100.0,
-            completed_hours: 20.0,
-        });
+    fn test_suggest_sessions_skips_dnd() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 30.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.set("dnd", "08:00-17:00").unwrap();
+
+        let proposed = scheduler.suggest_sessions(10.0, 90).unwrap();
+        assert_eq!(proposed.len(), 7);
+        for (_, session) in &proposed {
+            let start = NaiveTime::parse_from_str(&session.start_time, "%H:%M").unwrap();
+            assert!(start >= NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+        }
+    }
 
-        scheduler.config.subjects.insert("dsa".to_string(), Subject {
-            target_hours: 20.0,
-            completed_hours: 15.0,
-        });
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(Scheduler::format_countdown(chrono::Duration::seconds(65)), "01:05");
+        assert_eq!(Scheduler::format_countdown(chrono::Duration::seconds(3725)), "1:02:05");
+        assert_eq!(Scheduler::format_countdown(chrono::Duration::seconds(-3)), "00:00");
+    }
 
-        scheduler.show_progress();
+    #[tokio::test]
+    async fn test_focus_rejects_unknown_subject() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+
+        let result = scheduler.focus("s.a", 25).await;
+        assert!(matches!(result, Err(StudyTimerError::SubjectNotFound(_))));
+
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        let result = scheduler.focus("sys arch", 0).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_focus() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        assert!(matches!(scheduler.resume_focus().await, Err(StudyTimerError::Session(_))));
+
+        // an hour long session that died as it ended, half an hour ago
+        let now = Local::now();
+        let state = crate::schedule::SessionState {
+            subject: "sys arch".to_string(),
+            start_time: now - chrono::Duration::minutes(90),
+            end_time: now - chrono::Duration::minutes(30),
+            paused_seconds: 0,
+            saved_at: now - chrono::Duration::minutes(30),
+        };
+        scheduler.session_file.save(&state).unwrap();
+
+        let result = scheduler.focus("sys arch", 25).await;
+        assert!(matches!(result, Err(StudyTimerError::Session(_))));
+
+        let (subject, hours) = scheduler.resume_focus().await.unwrap();
+        assert_eq!(subject, "sys arch");
+        assert!((hours - 1.0).abs() < 0.01);
+        assert!((scheduler.config.subjects.get("sys arch").unwrap().completed_hours - 1.0).abs() < 0.01);
+        assert!(scheduler.session_file.load().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pomodoro_validates_input() {
+        let (_temp_dir, config_path) = test_config_path();
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        assert!(matches!(scheduler.pomodoro("s.a", 25, 5, 4).await, Err(StudyTimerError::SubjectNotFound(_))));
+
+        scheduler.add_subject("sys arch", 100.0, 0.0, SubjectOptions::default()).unwrap();
+        assert!(matches!(scheduler.pomodoro("sys arch", 0, 5, 4).await, Err(StudyTimerError::InvalidDuration(_))));
+        assert!(matches!(scheduler.pomodoro("sys arch", 25, 5, 0).await, Err(StudyTimerError::InvalidDuration(_))));
+        assert_eq!(scheduler.config.subjects.get("sys arch").unwrap().completed_hours, 0.0);
     }
 
     #[test]
-    fn test_generate_progress_bar() {
-        let scheduler = Scheduler::init().unwrap();
+    fn test_format_minutes() {
+        assert_eq!(Scheduler::format_minutes(5), "5m");
+        assert_eq!(Scheduler::format_minutes(72), "1h12m");
+        assert_eq!(Scheduler::format_minutes(24 * 60 + 61), "1d1h1m");
+    }
 
-        let bar_0 = scheduler.generate_progress_bar(0.0);
-        assert!(bar_0.contains("░"));
-        assert!(!bar_0.contains("█"));
-        
-        let bar_50 = scheduler.generate_progress_bar(50.0);
+    #[test]
+    fn test_duration_until_next_minute() {
+        use chrono::TimeZone;
 
-        let bar_100 = scheduler.generate_progress_bar(100.0);
-        assert!(bar_100.contains("█"));
-        assert!(!bar_100.contains("░"));
+        let now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+        assert_eq!(Scheduler::duration_until_next_minute(&now), Duration::from_secs(60));
+
+        let now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 30).unwrap();
+        assert_eq!(Scheduler::duration_until_next_minute(&now), Duration::from_secs(30));
+
+        let now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 30).unwrap().with_nanosecond(500_000_000).unwrap();
+        assert_eq!(Scheduler::duration_until_next_minute(&now), Duration::from_millis(29_500));
+
+        let now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 59).unwrap().with_nanosecond(999_000_000).unwrap();
+        assert_eq!(Scheduler::duration_until_next_minute(&now), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_tick_does_not_drift() {
+        use chrono::TimeZone;
+
+        // simulate passes that take a few seconds each and check every wake lands on :00
+        let mut now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 7).unwrap();
+        for _ in 0..120 {
+            now += chrono::Duration::milliseconds(2_345);
+            let tick = Scheduler::duration_until_next_minute(&now);
+            now += chrono::Duration::from_std(tick).unwrap();
+
+            assert_eq!(now.second(), 0);
+            assert_eq!(now.nanosecond(), 0);
+        }
     }
 
     #[tokio::test]
     async fn test_run_daemon_basic() {
-        let mut scheduler = Scheduler::init().unwrap();
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        // 2024-01-15 is a Monday
+        let now = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+
+        // only the session starting this minute fires, the one an hour later doesn't
+        let mut config = create_test_config();
+        config.config_path = temp_dir.path().join("config.json");
+        config.subjects.insert("compilers".to_string(), Subject {
+            target_hours: 10.0,
+            completed_hours: 0.0,
+            last_studied: None,
+            deadline: None,
+            weekly_target: None,
+            weekly_completed: 0.0,
+            tags: Vec::new(),
+        });
+        config.schedules.insert("message queues".to_string(), vec![StudySession::new("Monday", "09:00", 30)]);
+        config.schedules.insert("compilers".to_string(), vec![StudySession::new("Monday", "10:00", 30)]);
+
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify()
+            .withf(|title, message, _| title == "Study Timer" && message == "Time to study message queues for 30 minutes")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let mut scheduler = Scheduler::with_notifier(config, Arc::new(notifier))
+            .with_pid_file(PidFile::from_path(temp_dir.path().join("test.pid")))
+            .with_clock(move || now);
 
         let result = scheduler.run_daemon().await;
         assert!(result.is_ok());
 
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         scheduler.stop_daemon().unwrap();
 
+        let handle = result.unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), handle).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_daemon_credits_one_off_across_midnight() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let now = Local.with_ymd_and_hms(2024, 1, 16, 0, 30, 0).unwrap();
+
+        // started on the monday and ends as the tuesday begins, it must still be there to credit
+        let mut config = create_test_config();
+        config.config_path = temp_dir.path().join("config.json");
+        config.schedules.insert("message queues".to_string(), vec![StudySession::once(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), "23:30", 60)]);
+
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify()
+            .withf(|_, message, _| message == "message queues session done, 60 minutes logged")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let mut scheduler = Scheduler::with_notifier(config, Arc::new(notifier))
+            .with_pid_file(PidFile::from_path(temp_dir.path().join("test.pid")))
+            .with_clock(move || now);
+
+        let handle = scheduler.run_daemon().await.unwrap();
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        scheduler.stop_daemon().unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), handle).await.unwrap().unwrap();
+
+        let saved = Config::load_from(&temp_dir.path().join("config.json")).unwrap();
+        assert_eq!(saved.subjects["message queues"].completed_hours, 1.0);
+
+        let entries = scheduler.history.read().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].start, entries[0].end), (Local.with_ymd_and_hms(2024, 1, 15, 23, 30, 0).unwrap(), now));
+    }
+
+    #[test]
+    fn test_daemon_notifier_counts_failures() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile::from_path(temp_dir.path().join("test.pid"));
+
+        let failing = Arc::new(AtomicBool::new(true));
+        let mut notifier = MockNotifier::new();
+        let fail = Arc::clone(&failing);
+        notifier.expect_notify()
+            .returning(move |_, _, _| if fail.load(Ordering::SeqCst) { Err("no notification daemon".into()) } else { Ok(()) });
+
+        let metrics = Arc::new(DaemonMetrics::default());
+        let mut daemon_notifier = DaemonNotifier::new(Arc::new(notifier), pid_file.clone(), Arc::clone(&metrics));
+        for _ in 0..NOTIFY_FALLBACK_AFTER {
+            daemon_notifier.send("Study Timer", "Time to study dsa", None);
+        }
+
+        let health = pid_file.read_health();
+        assert_eq!(health.failed_notifications, NOTIFY_FALLBACK_AFTER as u64);
+        assert_eq!(health.consecutive_failures, NOTIFY_FALLBACK_AFTER);
+        assert_eq!(health.last_error.as_deref(), Some("no notification daemon"));
+
+        // a success ends the streak but the total is kept
+        failing.store(false, Ordering::SeqCst);
+        daemon_notifier.send("Study Timer", "Time to study dsa", None);
+        let health = pid_file.read_health();
+        assert_eq!(health.failed_notifications, NOTIFY_FALLBACK_AFTER as u64);
+        assert_eq!(health.consecutive_failures, 0);
+
+        assert_eq!(metrics.notifications_sent.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.notifications_failed.load(Ordering::Relaxed), NOTIFY_FALLBACK_AFTER as u64);
+    }
+
+    #[test]
+    fn test_daemon_notifier_skips_fallback_when_shown_locally() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let pid_file = PidFile::from_path(temp_dir.path().join("test.pid"));
+
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify()
+            .returning(|_, _, _| Err(Box::new(ShownLocally("webhook unreachable".into()))));
+        let mut fallback = MockNotifier::new();
+        fallback.expect_notify().times(0);
+
+        let mut daemon_notifier = DaemonNotifier::new(Arc::new(notifier), pid_file.clone(), Arc::new(DaemonMetrics::default()));
+        daemon_notifier.fallback = Box::new(fallback);
+        for _ in 0..NOTIFY_FALLBACK_AFTER + 1 {
+            daemon_notifier.send("Study Timer", "Time to study dsa", None);
+        }
+
+        // still counted, only not printed a second time
+        assert_eq!(pid_file.read_health().consecutive_failures, NOTIFY_FALLBACK_AFTER + 1);
+    }
+
+    #[test]
+    fn test_test_notification() {
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify()
+            .with(eq("Study Timer"), eq("test notification"), eq(None))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let scheduler = Scheduler::with_notifier(create_test_config(), Arc::new(notifier));
+        assert!(scheduler.test_notification().is_ok());
+
+        let mut notifier = MockNotifier::new();
+        notifier.expect_notify()
+            .times(1)
+            .returning(|_, _, _| Err("no notification daemon".into()));
+        let scheduler = Scheduler::with_notifier(create_test_config(), Arc::new(notifier));
+        let result = scheduler.test_notification();
+        assert!(matches!(result, Err(StudyTimerError::Notification(ref e)) if e == "no notification daemon"));
+    }
+
+    #[tokio::test]
+    async fn test_run_daemon_stops_when_running_flips() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let mut scheduler = Scheduler::init(&temp_dir.path().join("config.json"))
+            .unwrap()
+            .with_pid_file(PidFile::from_path(temp_dir.path().join("test.pid")));
+
+        let handle = scheduler.run_daemon().await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        scheduler.stop_daemon().unwrap();
+
+        let result = tokio::time::timeout(tokio::time::Duration::from_secs(1), handle).await;
+        assert!(result.is_ok());
+        assert!(!scheduler.running.load(std::sync::atomic::Ordering::SeqCst));
     }
 }
 
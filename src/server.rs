@@ -0,0 +1,168 @@
+use crate::error::StudyTimerError;
+use crate::scheduler::Scheduler;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task;
+use tracing::info;
+
+#[derive(Deserialize)]
+struct TagQuery {
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LogRequest {
+    subject: String,
+    hours: f32,
+}
+
+pub async fn serve(config_path: PathBuf, host: IpAddr, port: u16) -> Result<(), StudyTimerError> {
+    let listener = TcpListener::bind(SocketAddr::new(host, port)).await?;
+    info!(address = %listener.local_addr()?, "serving the http api");
+
+    axum::serve(listener, router(config_path)).await?;
+    Ok(())
+}
+
+pub fn router(config_path: PathBuf) -> Router {
+    Router::new()
+        .route("/subjects", get(subjects))
+        .route("/progress", get(progress))
+        .route("/next", get(next))
+        .route("/log", post(log))
+        .with_state(Arc::new(config_path))
+}
+
+async fn subjects(State(config_path): State<Arc<PathBuf>>, Query(query): Query<TagQuery>) -> Response {
+    respond(config_path, move |scheduler| Ok(serde_json::to_value(scheduler.subject_reports(query.tag.as_deref()))?)).await
+}
+
+async fn progress(State(config_path): State<Arc<PathBuf>>, Query(query): Query<TagQuery>) -> Response {
+    respond(config_path, move |scheduler| Ok(serde_json::to_value(scheduler.progress_report(query.tag.as_deref()))?)).await
+}
+
+// null when nothing is scheduled in the next week
+async fn next(State(config_path): State<Arc<PathBuf>>) -> Response {
+    respond(config_path, |scheduler| Ok(serde_json::to_value(scheduler.next_report(&Local::now().naive_local()))?)).await
+}
+
+// answers with the subject as it is after logging
+async fn log(State(config_path): State<Arc<PathBuf>>, Json(request): Json<LogRequest>) -> Response {
+    respond(config_path, move |scheduler| {
+        scheduler.log_hours(&request.subject, request.hours)?;
+
+        let report = scheduler.subject_reports(None).into_iter().find(|report| report.name == request.subject);
+        Ok(serde_json::to_value(report)?)
+    })
+    .await
+}
+
+// every request reads the config afresh, so changes made from the command line or by the daemon show up.
+// the config lock can block, which is why this runs off the async workers
+async fn respond<F>(config_path: Arc<PathBuf>, handler: F) -> Response
+where
+    F: FnOnce(&mut Scheduler) -> Result<Value, StudyTimerError> + Send + 'static,
+{
+    let result = task::spawn_blocking(move || {
+        let mut scheduler = Scheduler::new(&config_path)?;
+        handler(&mut scheduler)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(body)) => Json(body).into_response(),
+        Ok(Err(e)) => (status(&e), Json(json!({ "error": e.to_string() }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+fn status(e: &StudyTimerError) -> StatusCode {
+    match e {
+        StudyTimerError::SubjectNotFound(_) => StatusCode::NOT_FOUND,
+        StudyTimerError::InvalidHours(_) => StatusCode::BAD_REQUEST,
+        StudyTimerError::ConfigBusy(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::scheduler::SubjectOptions;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    async fn call(router: Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post_log(body: &str) -> Request<Body> {
+        Request::post("/log")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("config.json");
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 10.0, 2.0, SubjectOptions::default()).unwrap();
+        scheduler.add_subject("os", 10.0, 0.0, SubjectOptions::default()).unwrap();
+        scheduler.add_schedule("dsa", "Monday", "09:00", 60, None).unwrap();
+
+        let router = router(config_path.clone());
+
+        let (status, body) = call(router.clone(), Request::get("/subjects").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["name"], "dsa");
+        assert_eq!(body[0]["sessions"][0]["start_time"], "09:00");
+
+        let (_, body) = call(router.clone(), Request::get("/progress").body(Body::empty()).unwrap()).await;
+        assert_eq!(body["total_target_hours"], 20.0);
+        assert_eq!(body["overall_percentage"], 10.0);
+
+        let (_, body) = call(router.clone(), Request::get("/next").body(Body::empty()).unwrap()).await;
+        assert_eq!(body["subject"], "dsa");
+
+        let (status, body) = call(router.clone(), post_log(r#"{"subject": "dsa", "hours": 1.5}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["completed_hours"], 3.5);
+        assert_eq!(Config::load_from(&config_path).unwrap().subjects["dsa"].completed_hours, 3.5);
+    }
+
+    #[tokio::test]
+    async fn test_log_errors() {
+        let temp_dir = tempdir().expect("failed to create temp directory");
+        let config_path = temp_dir.path().join("config.json");
+        let mut scheduler = Scheduler::init(&config_path).unwrap();
+        scheduler.add_subject("dsa", 10.0, 0.0, SubjectOptions::default()).unwrap();
+
+        let router = router(config_path);
+
+        let (status, body) = call(router.clone(), post_log(r#"{"subject": "os", "hours": 1}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body["error"].as_str().unwrap().contains("'os'"));
+
+        let (status, _) = call(router, post_log(r#"{"subject": "dsa", "hours": -1}"#)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
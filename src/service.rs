@@ -0,0 +1,96 @@
+use clap::ValueEnum;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ServiceKind {
+    Systemd,
+}
+
+// the unit for the service manager, running `start` in the foreground so the manager owns the process
+pub fn unit(kind: ServiceKind, exe: &Path, profile: Option<&str>, config: Option<&Path>) -> String {
+    match kind {
+        ServiceKind::Systemd => systemd_unit(exe, profile, config),
+    }
+}
+
+pub fn unit_name(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("study_timer-{}.service", profile),
+        None => "study_timer.service".to_string(),
+    }
+}
+
+fn systemd_unit(exe: &Path, profile: Option<&str>, config: Option<&Path>) -> String {
+    let mut args = vec![exe.to_string_lossy().into_owned()];
+    if let Some(profile) = profile {
+        args.push("--profile".to_string());
+        args.push(profile.to_string());
+    }
+    if let Some(config) = config {
+        args.push("--config".to_string());
+        args.push(config.to_string_lossy().into_owned());
+    }
+    args.push("start".to_string());
+
+    let exec_start: Vec<String> = args.iter().map(|arg| systemd_quote(arg)).collect();
+    let name = unit_name(profile);
+
+    [
+        format!("# save as ~/.config/systemd/user/{}", name),
+        format!("# then run: systemctl --user enable --now {}", name),
+        "[Unit]".to_string(),
+        "Description=study timer reminders".to_string(),
+        "After=graphical-session.target".to_string(),
+        String::new(),
+        "[Service]".to_string(),
+        "Type=simple".to_string(),
+        format!("ExecStart={}", exec_start.join(" ")),
+        "Restart=on-failure".to_string(),
+        String::new(),
+        "[Install]".to_string(),
+        "WantedBy=default.target".to_string(),
+    ]
+    .join("\n") + "\n"
+}
+
+// systemd splits ExecStart on whitespace and expands % specifiers, so quote and escape as needed
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+
+    if escaped.is_empty() || escaped.contains([' ', '\t', '"', '\'', '\\']) {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit() {
+        let unit = unit(ServiceKind::Systemd, Path::new("/usr/local/bin/study_timer"), None, None);
+
+        assert!(unit.contains("\nExecStart=/usr/local/bin/study_timer start\n"));
+        assert!(unit.contains("\nRestart=on-failure\n"));
+        assert!(unit.contains("\n[Install]\nWantedBy=default.target\n"));
+        assert!(unit.contains("systemctl --user enable --now study_timer.service"));
+    }
+
+    #[test]
+    fn test_systemd_unit_with_profile_and_config() {
+        let unit = unit(ServiceKind::Systemd, Path::new("/home/me/my bin/study_timer"), Some("uni"), Some(Path::new("/home/me/100%.json")));
+
+        assert!(unit.contains("\nExecStart=\"/home/me/my bin/study_timer\" --profile uni --config /home/me/100%%.json start\n"));
+        assert!(unit.contains("~/.config/systemd/user/study_timer-uni.service"));
+    }
+
+    #[test]
+    fn test_systemd_quote() {
+        assert_eq!(systemd_quote("plain"), "plain");
+        assert_eq!(systemd_quote("with space"), "\"with space\"");
+        assert_eq!(systemd_quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(systemd_quote(""), "\"\"");
+    }
+}
@@ -0,0 +1,217 @@
+use crate::daemon::PidFile;
+use crate::error::StudyTimerError;
+use crate::scheduler::{DaemonState, Dashboard, Scheduler};
+use chrono::{Local, NaiveDateTime};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::Path;
+use std::time::Duration;
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+// read-only, the config is read again on every refresh so changes from other commands and the daemon show up
+pub fn run(config_path: &Path, pid_file: PidFile, ascii: bool) -> Result<(), StudyTimerError> {
+    // a config that can't be read fails before the terminal is taken over
+    let dashboard = load(config_path, &pid_file, ascii)?;
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, dashboard, config_path, &pid_file, ascii);
+    ratatui::restore();
+
+    result
+}
+
+fn load(config_path: &Path, pid_file: &PidFile, ascii: bool) -> Result<(Dashboard, NaiveDateTime), StudyTimerError> {
+    let now = Local::now().naive_local();
+    let scheduler = Scheduler::new(config_path)?
+        .with_pid_file(pid_file.clone())
+        .with_ascii(ascii);
+
+    Ok((scheduler.dashboard(&now)?, now))
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut dashboard: (Dashboard, NaiveDateTime), config_path: &Path, pid_file: &PidFile, ascii: bool) -> Result<(), StudyTimerError> {
+    loop {
+        terminal.draw(|frame| draw(frame, &dashboard.0, dashboard.1))?;
+
+        if event::poll(REFRESH)? && let Event::Key(key) = event::read()? && quits(&key) {
+            return Ok(());
+        }
+
+        // a failed read, like a save holding the lock, keeps the last good frame on screen
+        if let Ok(latest) = load(config_path, pid_file, ascii) {
+            dashboard = latest;
+        }
+    }
+}
+
+fn quits(key: &KeyEvent) -> bool {
+    key.kind == KeyEventKind::Press
+        && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)))
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, now: NaiveDateTime) {
+    let today_height = dashboard.today.len().max(1) as u16 + 2;
+    let [header, subjects, today, footer] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Min(3),
+        Constraint::Length(today_height),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(Paragraph::new(header_lines(dashboard)).block(Block::bordered().title(" study timer ")), header);
+    frame.render_widget(Paragraph::new(subject_lines(dashboard)).block(Block::bordered().title(" subjects ")), subjects);
+    frame.render_widget(Paragraph::new(today_lines(dashboard)).block(Block::bordered().title(format!(" today, {} ", now.format("%A %H:%M:%S")))), today);
+    frame.render_widget(Paragraph::new(Line::styled("q to quit, refreshes every second", Style::new().fg(Color::DarkGray))), footer);
+}
+
+fn header_lines(dashboard: &Dashboard) -> Vec<Line<'static>> {
+    let mut daemon = match dashboard.daemon {
+        DaemonState::Running { pid, .. } => vec![Span::raw("daemon: "), Span::styled(format!("running (pid {})", pid), Style::new().fg(Color::Green))],
+        DaemonState::Stale(pid) => vec![Span::raw("daemon: "), Span::styled(format!("not running (stale pid file for pid {})", pid), Style::new().fg(Color::Red))],
+        DaemonState::Stopped => vec![Span::raw("daemon: "), Span::styled("not running", Style::new().fg(Color::Red))],
+    };
+    if dashboard.paused {
+        daemon.push(Span::styled(", reminders paused", Style::new().fg(Color::Yellow)));
+    }
+
+    let next = match &dashboard.next {
+        Some((subject, session, 0)) => Line::from(vec![
+            Span::raw("next: "),
+            Span::styled(subject.clone(), Style::new().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" at {} starts now", session.start_time)),
+        ]),
+        Some((subject, session, seconds)) => Line::from(vec![
+            Span::raw("next: "),
+            Span::styled(subject.clone(), Style::new().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" on {} at {} in ", session.describe_day(), session.start_time)),
+            Span::styled(countdown(*seconds), Style::new().add_modifier(Modifier::BOLD)),
+        ]),
+        None => Line::raw("nothing scheduled in the next 7 days"),
+    };
+
+    vec![Line::from(daemon), next]
+}
+
+fn subject_lines(dashboard: &Dashboard) -> Vec<Line<'static>> {
+    if dashboard.subjects.is_empty() {
+        return vec![Line::raw("no subjects yet, add one with 'study_timer add'")];
+    }
+
+    let width = dashboard.subjects.iter().map(|subject| subject.name.chars().count()).max().unwrap_or(0);
+
+    dashboard.subjects
+        .iter()
+        .map(|subject| Line::from(vec![
+            Span::styled(format!("{:<width$} ", subject.name, width = width), Style::new().add_modifier(Modifier::BOLD)),
+            Span::raw("["),
+            Span::styled(subject.bar.fill.repeat(subject.bar.filled), Style::new().fg(bar_color(subject.bar.color))),
+            Span::raw(subject.bar.blank.repeat(subject.bar.empty)),
+            Span::raw("] "),
+            Span::raw(format!("{:.1}/{:.1}h {}", subject.completed_hours, subject.target_hours, Scheduler::format_percentage(subject.percentage))),
+        ]))
+        .collect()
+}
+
+fn today_lines(dashboard: &Dashboard) -> Vec<Line<'static>> {
+    if dashboard.today.is_empty() {
+        return vec![Line::raw("nothing scheduled today")];
+    }
+
+    dashboard.today
+        .iter()
+        .map(|(subject, session, ended)| {
+            let line = format!("{}-{} {} for {} minutes", session.start_time, session.end_time, subject, session.duration);
+            if *ended {
+                Line::styled(line, Style::new().fg(Color::DarkGray))
+            } else {
+                Line::raw(line)
+            }
+        })
+        .collect()
+}
+
+// HH:MM:SS, with days in front once it's a day or more away
+fn countdown(seconds: i64) -> String {
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let clock = format!("{:02}:{:02}:{:02}", rest / 3600, rest % 3600 / 60, rest % 60);
+
+    if days > 0 {
+        format!("{}d {}", days, clock)
+    } else {
+        clock
+    }
+}
+
+// the bar colors come from colored, which the rest of the output uses
+fn bar_color(color: colored::Color) -> Color {
+    match color {
+        colored::Color::Red => Color::Red,
+        colored::Color::Yellow => Color::Yellow,
+        colored::Color::Green => Color::Green,
+        _ => Color::Reset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StudySession;
+    use crate::scheduler::{DashboardSubject, ProgressBar};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn rendered(dashboard: &Dashboard) -> String {
+        let now = NaiveDateTime::parse_from_str("2024-01-15 08:00", "%Y-%m-%d %H:%M").unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(80, 16)).unwrap();
+        terminal.draw(|frame| draw(frame, dashboard, now)).unwrap();
+
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_draw() {
+        let dashboard = Dashboard {
+            daemon: DaemonState::Running { pid: 42, since: None },
+            paused: false,
+            subjects: vec![DashboardSubject {
+                name: "dsa".to_string(),
+                completed_hours: 5.0,
+                target_hours: 10.0,
+                percentage: Some(50.0),
+                bar: ProgressBar { filled: 15, empty: 15, fill: "#", blank: "-", color: colored::Color::Yellow },
+            }],
+            today: vec![("dsa".to_string(), StudySession::new("Monday", "09:00", 60), false)],
+            next: Some(("dsa".to_string(), StudySession::new("Monday", "09:00", 60), 3600)),
+        };
+
+        let screen = rendered(&dashboard);
+        assert!(screen.contains("running (pid 42)"));
+        assert!(screen.contains("in 01:00:00"));
+        assert!(screen.contains(&format!("[{}{}] 5.0/10.0h 50.0%", "#".repeat(15), "-".repeat(15))));
+        assert!(screen.contains("09:00-10:00 dsa for 60 minutes"));
+    }
+
+    #[test]
+    fn test_draw_empty() {
+        let dashboard = Dashboard { daemon: DaemonState::Stopped, paused: true, subjects: Vec::new(), today: Vec::new(), next: None };
+
+        let screen = rendered(&dashboard);
+        assert!(screen.contains("not running, reminders paused"));
+        assert!(screen.contains("no subjects yet"));
+        assert!(screen.contains("nothing scheduled today"));
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(countdown(59), "00:00:59");
+        assert_eq!(countdown(3 * 3600 + 61), "03:01:01");
+        assert_eq!(countdown(86_400 + 60), "1d 00:01:00");
+    }
+}